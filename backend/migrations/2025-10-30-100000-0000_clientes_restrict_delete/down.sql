@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_reservas_cliente;
+
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS reservas_cliente_id_fkey;
+
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_cliente_id_fkey
+FOREIGN KEY (cliente_id) REFERENCES clientes(id) ON DELETE CASCADE;
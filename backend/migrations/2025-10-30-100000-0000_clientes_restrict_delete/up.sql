@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- =========================================
+-- 🧍 Proteger el historial de reservas de los clientes
+-- =========================================
+-- Antes: borrar un cliente eliminaba en cascada todas sus reservas.
+-- Ahora: la base de datos rechaza el borrado si el cliente tiene reservas.
+
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS reservas_cliente_id_fkey;
+
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_cliente_id_fkey
+FOREIGN KEY (cliente_id) REFERENCES clientes(id) ON DELETE RESTRICT;
+
+CREATE INDEX IF NOT EXISTS idx_reservas_cliente ON reservas (cliente_id);
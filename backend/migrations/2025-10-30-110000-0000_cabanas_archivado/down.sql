@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS reservas_cabana_id_fkey;
+
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_cabana_id_fkey
+FOREIGN KEY (cabana_id) REFERENCES cabanas(id) ON DELETE CASCADE;
+
+ALTER TABLE cabanas
+DROP COLUMN IF EXISTS archivada;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- =========================================
+-- 🏠 Archivado de cabañas
+-- =========================================
+-- Una cabaña archivada deja de aparecer para reservar, pero sus
+-- reservas pasadas se conservan. Por eso el borrado físico de una
+-- cabaña con reservas ya no se propaga en cascada.
+
+ALTER TABLE cabanas
+ADD COLUMN archivada BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS reservas_cabana_id_fkey;
+
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_cabana_id_fkey
+FOREIGN KEY (cabana_id) REFERENCES cabanas(id) ON DELETE RESTRICT;
@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+-- ⚠️ Falla si existen reservas que cruzan la medianoche
+DROP INDEX IF EXISTS idx_reservas_cabana_inicio;
+
+ALTER TABLE reservas
+DROP COLUMN IF EXISTS fin,
+DROP COLUMN IF EXISTS inicio;
+
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS chk_horas;
+
+ALTER TABLE reservas
+ADD CONSTRAINT chk_horas CHECK (hora_fin > hora_inicio);
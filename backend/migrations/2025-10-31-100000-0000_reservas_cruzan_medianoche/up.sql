@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- =========================================
+-- 🌙 Reservas que cruzan la medianoche
+-- =========================================
+-- `fecha_reserva` sigue siendo la noche de la reserva. Si `hora_fin` es
+-- menor o igual que `hora_inicio`, la reserva termina al día siguiente
+-- (p. ej. 22:00 → 02:00). Los instantes reales quedan en `inicio` / `fin`,
+-- calculados por la base de datos, y son los que se usan para detectar
+-- solapamientos.
+
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS chk_horas;
+
+ALTER TABLE reservas
+ADD CONSTRAINT chk_horas CHECK (hora_fin <> hora_inicio);
+
+ALTER TABLE reservas
+ADD COLUMN inicio TIMESTAMP NOT NULL
+    GENERATED ALWAYS AS (fecha_reserva + hora_inicio) STORED,
+ADD COLUMN fin TIMESTAMP NOT NULL
+    GENERATED ALWAYS AS (
+        fecha_reserva + hora_fin
+        + CASE WHEN hora_fin <= hora_inicio THEN INTERVAL '1 day' ELSE INTERVAL '0' END
+    ) STORED;
+
+CREATE INDEX idx_reservas_cabana_inicio ON reservas (cabana_id, inicio, fin);
@@ -0,0 +1,17 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE cabanas ALTER COLUMN estado DROP DEFAULT;
+ALTER TABLE cabanas ALTER COLUMN estado TYPE VARCHAR(20) USING estado::text;
+ALTER TABLE cabanas ALTER COLUMN estado SET DEFAULT 'disponible';
+ALTER TABLE cabanas
+ADD CONSTRAINT cabanas_estado_check
+CHECK (estado IN ('disponible', 'ocupada', 'mantenimiento'));
+
+ALTER TABLE reservas ALTER COLUMN estado DROP DEFAULT;
+ALTER TABLE reservas ALTER COLUMN estado TYPE VARCHAR(20) USING estado::text;
+ALTER TABLE reservas ALTER COLUMN estado SET DEFAULT 'pendiente';
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_estado_check
+CHECK (estado IN ('pendiente', 'confirmada', 'en curso', 'completada', 'cancelada'));
+
+DROP TYPE IF EXISTS estado_cabana;
+DROP TYPE IF EXISTS estado_reserva;
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+-- =========================================
+-- 🏷️ Estados como tipos ENUM de PostgreSQL
+-- =========================================
+-- Única fuente de verdad para los estados válidos (antes había CHECKs que
+-- no coincidían con el backend: `confirmada` se había perdido).
+--
+-- Reservas:
+-- - pendiente: creada pero aún no iniciada
+-- - confirmada: el cliente confirmó su asistencia
+-- - en curso: hora actual dentro del rango
+-- - completada: hora actual superó el fin
+-- - cancelada: cancelada manualmente
+--
+-- Cabañas:
+-- - disponible / ocupada / mantenimiento
+
+CREATE TYPE estado_reserva AS ENUM ('pendiente', 'confirmada', 'en curso', 'completada', 'cancelada');
+CREATE TYPE estado_cabana AS ENUM ('disponible', 'ocupada', 'mantenimiento');
+
+ALTER TABLE reservas DROP CONSTRAINT IF EXISTS reservas_estado_check;
+ALTER TABLE reservas ALTER COLUMN estado DROP DEFAULT;
+ALTER TABLE reservas ALTER COLUMN estado TYPE estado_reserva USING estado::estado_reserva;
+ALTER TABLE reservas ALTER COLUMN estado SET DEFAULT 'pendiente';
+
+ALTER TABLE cabanas DROP CONSTRAINT IF EXISTS cabanas_estado_check;
+ALTER TABLE cabanas ALTER COLUMN estado DROP DEFAULT;
+ALTER TABLE cabanas ALTER COLUMN estado TYPE estado_cabana USING estado::estado_cabana;
+ALTER TABLE cabanas ALTER COLUMN estado SET DEFAULT 'disponible';
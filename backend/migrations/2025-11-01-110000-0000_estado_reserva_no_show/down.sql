@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+-- PostgreSQL no permite quitar valores de un ENUM: se recrea el tipo.
+-- Las reservas marcadas como no_show pasan a cancelada.
+UPDATE reservas SET estado = 'cancelada' WHERE estado = 'no_show';
+
+ALTER TABLE reservas ALTER COLUMN estado DROP DEFAULT;
+ALTER TYPE estado_reserva RENAME TO estado_reserva_old;
+CREATE TYPE estado_reserva AS ENUM ('pendiente', 'confirmada', 'en curso', 'completada', 'cancelada');
+ALTER TABLE reservas ALTER COLUMN estado TYPE estado_reserva USING estado::text::estado_reserva;
+ALTER TABLE reservas ALTER COLUMN estado SET DEFAULT 'pendiente';
+DROP TYPE estado_reserva_old;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- =========================================
+-- 🚫 Nuevo estado de reserva: no_show
+-- =========================================
+-- El cliente no se presentó. Las transiciones permitidas entre estados
+-- se controlan en el backend (EstadoReserva::transiciones).
+
+ALTER TYPE estado_reserva ADD VALUE IF NOT EXISTS 'no_show' AFTER 'cancelada';
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas
+DROP CONSTRAINT IF EXISTS reservas_sin_solapes;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- =========================================
+-- 🔒 Protección contra reservas dobles a nivel de base de datos
+-- =========================================
+-- El chequeo `existe_conflicto` del backend no basta si dos anfitriones
+-- reservan la misma cabaña a la vez (ambos pasan el chequeo antes de
+-- insertar). Esta restricción hace que PostgreSQL rechace cualquier
+-- solapamiento de [inicio, fin) en la misma cabaña, salvo reservas
+-- canceladas o no presentadas.
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+ALTER TABLE reservas
+ADD CONSTRAINT reservas_sin_solapes
+EXCLUDE USING gist (
+    cabana_id WITH =,
+    tsrange(inicio, fin, '[)') WITH &&
+) WHERE (estado NOT IN ('cancelada', 'no_show'));
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE cabanas DROP COLUMN IF EXISTS ocupacion_minima;
+ALTER TABLE reservas DROP COLUMN IF EXISTS num_personas;
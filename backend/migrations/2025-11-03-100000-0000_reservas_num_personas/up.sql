@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- =========================================
+-- 👥 Número de personas por reserva
+-- =========================================
+-- `num_personas` se valida contra la capacidad de la cabaña y contra su
+-- ocupación mínima (para no bloquear una cabaña VIP grande con una pareja).
+
+ALTER TABLE reservas
+ADD COLUMN num_personas INT NOT NULL DEFAULT 1
+    CONSTRAINT chk_num_personas CHECK (num_personas > 0);
+
+ALTER TABLE cabanas
+ADD COLUMN ocupacion_minima INT NOT NULL DEFAULT 1
+    CONSTRAINT chk_ocupacion_minima CHECK (ocupacion_minima > 0 AND ocupacion_minima <= capacidad);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas DROP COLUMN IF EXISTS monto_total;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- =========================================
+-- 💰 Monto total congelado al reservar
+-- =========================================
+-- Se calcula en el backend (horas facturables × precio_hora) y no cambia
+-- aunque luego se modifique el precio de la cabaña. Queda NULL para
+-- reservas antiguas o cabañas sin precio configurado.
+
+ALTER TABLE reservas
+ADD COLUMN monto_total NUMERIC(10,2);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS feriados;
+DROP TABLE IF EXISTS reglas_precio;
+DROP TYPE IF EXISTS modo_regla_precio;
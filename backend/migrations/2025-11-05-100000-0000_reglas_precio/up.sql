@@ -0,0 +1,55 @@
+-- Your SQL goes here
+-- =========================================
+-- 📈 Reglas de precio dinámico
+-- =========================================
+-- Cada regla ajusta el precio por hora de una cabaña (cabana_id), de una
+-- zona (coincidencia exacta con cabanas.zona, p. ej. 'Zona A') o de todas (ambos NULL).
+--
+-- Condiciones (todas opcionales, se combinan con AND):
+-- - dias_semana: 1 = lunes ... 7 = domingo (ISO)
+-- - hora_desde / hora_hasta: franja horaria; si hora_hasta <= hora_desde
+--   cruza la medianoche y pertenece al día en que empieza
+--   (viernes 22:00–03:00 cubre la madrugada del sábado)
+-- - fecha_desde / fecha_hasta: rango de fechas
+-- - solo_feriados: solo los días cargados en `feriados`
+--
+-- modo:
+-- - multiplicador: precio_hora de la cabaña × valor
+-- - tarifa: valor es el precio por hora absoluto
+--
+-- Precedencia: gana UNA sola regla por minuto, la de mayor `prioridad`;
+-- a igual prioridad, la más específica (cabaña > zona > todas) y luego la
+-- más reciente.
+
+CREATE TYPE modo_regla_precio AS ENUM ('multiplicador', 'tarifa');
+
+CREATE TABLE reglas_precio (
+    id SERIAL PRIMARY KEY,
+    nombre VARCHAR(100) NOT NULL,
+    cabana_id INT REFERENCES cabanas(id) ON DELETE CASCADE,
+    zona VARCHAR(100),
+    dias_semana INT[],
+    hora_desde TIME,
+    hora_hasta TIME,
+    fecha_desde DATE,
+    fecha_hasta DATE,
+    solo_feriados BOOLEAN NOT NULL DEFAULT FALSE,
+    modo modo_regla_precio NOT NULL,
+    valor NUMERIC(10,2) NOT NULL CHECK (valor > 0),
+    prioridad INT NOT NULL DEFAULT 0,
+    activa BOOLEAN NOT NULL DEFAULT TRUE,
+    CONSTRAINT chk_regla_alcance CHECK (cabana_id IS NULL OR zona IS NULL),
+    CONSTRAINT chk_regla_franja CHECK ((hora_desde IS NULL) = (hora_hasta IS NULL)),
+    CONSTRAINT chk_regla_fechas CHECK (fecha_desde IS NULL OR fecha_hasta IS NULL OR fecha_desde <= fecha_hasta),
+    CONSTRAINT chk_regla_dias CHECK (dias_semana IS NULL OR dias_semana <@ ARRAY[1,2,3,4,5,6,7])
+);
+
+CREATE INDEX idx_reglas_precio_cabana ON reglas_precio (cabana_id);
+
+-- ===============================
+-- 🎉 FERIADOS
+-- ===============================
+CREATE TABLE feriados (
+    fecha DATE PRIMARY KEY,
+    nombre VARCHAR(100) NOT NULL
+);
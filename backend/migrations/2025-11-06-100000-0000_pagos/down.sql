@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas DROP COLUMN IF EXISTS saldo;
+ALTER TABLE reservas DROP COLUMN IF EXISTS monto_pagado;
+DROP TABLE IF EXISTS pagos;
+DROP TYPE IF EXISTS tipo_pago;
+DROP TYPE IF EXISTS metodo_pago;
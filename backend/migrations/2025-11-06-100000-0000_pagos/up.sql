@@ -0,0 +1,31 @@
+-- Your SQL goes here
+-- =========================================
+-- 💵 Pagos y reembolsos de reservas
+-- =========================================
+-- Cada movimiento queda registrado (nunca se edita ni se borra): las señas
+-- por QR, los pagos parciales y el cobro final son filas 'pago'; las
+-- devoluciones son filas 'reembolso'. El monto siempre es positivo.
+--
+-- reservas.monto_pagado acumula pagos - reembolsos y lo mantiene el backend
+-- en la misma transacción que inserta el movimiento; saldo se deriva solo.
+
+CREATE TYPE metodo_pago AS ENUM ('efectivo', 'tarjeta', 'qr');
+CREATE TYPE tipo_pago AS ENUM ('pago', 'reembolso');
+
+CREATE TABLE pagos (
+    id SERIAL PRIMARY KEY,
+    -- RESTRICT: una reserva con movimientos no se puede borrar, solo cancelar
+    reserva_id INT NOT NULL REFERENCES reservas(id) ON DELETE RESTRICT,
+    tipo tipo_pago NOT NULL DEFAULT 'pago',
+    monto NUMERIC(10,2) NOT NULL CHECK (monto > 0),
+    metodo metodo_pago NOT NULL,
+    referencia VARCHAR(100),
+    registrado_por VARCHAR(100) NOT NULL,
+    fecha_pago TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pagos_reserva ON pagos (reserva_id);
+
+ALTER TABLE reservas
+ADD COLUMN monto_pagado NUMERIC(10,2) NOT NULL DEFAULT 0 CHECK (monto_pagado >= 0),
+ADD COLUMN saldo NUMERIC(10,2) GENERATED ALWAYS AS (monto_total - monto_pagado) STORED;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS comprobantes;
+ALTER TABLE clientes DROP COLUMN IF EXISTS razon_social;
+ALTER TABLE clientes DROP COLUMN IF EXISTS nit;
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+-- =========================================
+-- 🧾 Comprobantes de reservas completadas
+-- =========================================
+-- Datos fiscales opcionales del cliente (sin NIT se emite a "S/N", NIT 0)
+ALTER TABLE clientes
+ADD COLUMN nit VARCHAR(20),
+ADD COLUMN razon_social VARCHAR(150);
+
+-- Cada comprobante congela los datos con los que se emitió: reimprimirlo
+-- siempre da el mismo documento aunque luego cambien el cliente o la cabaña.
+-- `numero` es correlativo y sin huecos; lo asigna el backend bloqueando la tabla.
+CREATE TABLE comprobantes (
+    id SERIAL PRIMARY KEY,
+    numero INT NOT NULL UNIQUE CHECK (numero > 0),
+    reserva_id INT NOT NULL UNIQUE REFERENCES reservas(id) ON DELETE RESTRICT,
+    fecha_emision TIMESTAMP NOT NULL DEFAULT NOW(),
+    nombre_cliente VARCHAR(100) NOT NULL,
+    nit VARCHAR(20),
+    razon_social VARCHAR(150),
+    descripcion TEXT NOT NULL,
+    -- total = base_imponible + impuesto (el precio de la reserva incluye impuesto)
+    base_imponible NUMERIC(10,2) NOT NULL,
+    tasa_impuesto NUMERIC(5,2) NOT NULL,
+    impuesto NUMERIC(10,2) NOT NULL,
+    total NUMERIC(10,2) NOT NULL
+);
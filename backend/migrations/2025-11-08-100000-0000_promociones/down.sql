@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas DROP COLUMN IF EXISTS descuento;
+DROP TABLE IF EXISTS canjes_promocion;
+DROP TABLE IF EXISTS promociones;
+DROP TYPE IF EXISTS tipo_descuento;
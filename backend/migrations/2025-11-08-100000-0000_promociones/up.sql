@@ -0,0 +1,55 @@
+-- Your SQL goes here
+-- =========================================
+-- 🏷️ Promociones y códigos de descuento
+-- =========================================
+-- Restricciones (todas opcionales, se combinan con AND):
+-- - vigente_desde / vigente_hasta: fechas de la reserva admitidas
+-- - dias_semana: 1 = lunes ... 7 = domingo, según fecha_reserva
+-- - hora_desde / hora_hasta: la reserva debe EMPEZAR en la franja
+--   (si hora_hasta <= hora_desde, la franja cruza la medianoche)
+-- - cabana_ids: cabañas en las que vale (NULL = todas)
+-- - usos_maximos / usos_por_cliente: canjes de reservas no canceladas
+-- - horas_minimas: duración mínima de la reserva
+--
+-- Los códigos se guardan en mayúsculas.
+
+CREATE TYPE tipo_descuento AS ENUM ('porcentaje', 'monto_fijo');
+
+CREATE TABLE promociones (
+    id SERIAL PRIMARY KEY,
+    codigo VARCHAR(30) NOT NULL UNIQUE CHECK (codigo = UPPER(codigo) AND codigo <> ''),
+    descripcion TEXT,
+    tipo tipo_descuento NOT NULL,
+    valor NUMERIC(10,2) NOT NULL CHECK (valor > 0),
+    vigente_desde DATE,
+    vigente_hasta DATE,
+    dias_semana INT[],
+    hora_desde TIME,
+    hora_hasta TIME,
+    cabana_ids INT[],
+    usos_maximos INT CHECK (usos_maximos > 0),
+    usos_por_cliente INT CHECK (usos_por_cliente > 0),
+    horas_minimas NUMERIC(4,2) CHECK (horas_minimas > 0),
+    activa BOOLEAN NOT NULL DEFAULT TRUE,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_promo_porcentaje CHECK (tipo <> 'porcentaje' OR valor <= 100),
+    CONSTRAINT chk_promo_franja CHECK ((hora_desde IS NULL) = (hora_hasta IS NULL)),
+    CONSTRAINT chk_promo_vigencia CHECK (vigente_desde IS NULL OR vigente_hasta IS NULL OR vigente_desde <= vigente_hasta),
+    CONSTRAINT chk_promo_dias CHECK (dias_semana IS NULL OR dias_semana <@ ARRAY[1,2,3,4,5,6,7])
+);
+
+-- Un canje por reserva; si la reserva se borra, el canje se libera
+CREATE TABLE canjes_promocion (
+    id SERIAL PRIMARY KEY,
+    promocion_id INT NOT NULL REFERENCES promociones(id) ON DELETE RESTRICT,
+    reserva_id INT NOT NULL UNIQUE REFERENCES reservas(id) ON DELETE CASCADE,
+    cliente_id INT NOT NULL REFERENCES clientes(id) ON DELETE RESTRICT,
+    descuento NUMERIC(10,2) NOT NULL CHECK (descuento >= 0),
+    fecha_canje TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_canjes_promocion ON canjes_promocion (promocion_id, cliente_id);
+
+-- monto_total pasa a ser el neto a cobrar; descuento guarda lo descontado
+ALTER TABLE reservas
+ADD COLUMN descuento NUMERIC(10,2) NOT NULL DEFAULT 0 CHECK (descuento >= 0);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS excepciones_horario;
+DROP TABLE IF EXISTS horarios_atencion;
@@ -0,0 +1,32 @@
+-- Your SQL goes here
+-- =========================================
+-- 🕘 Horario de atención y días de excepción
+-- =========================================
+-- Un horario por día de la semana (1 = lunes ... 7 = domingo).
+-- - cierra <= abre: la jornada termina al día siguiente (viernes 18:00–04:00)
+-- - cierra = abre: abierto las 24 horas
+-- - cerrado: no se atiende ese día
+-- Una reserva tiene que caer entera dentro de horas abiertas.
+
+CREATE TABLE horarios_atencion (
+    dia_semana INT PRIMARY KEY CHECK (dia_semana BETWEEN 1 AND 7),
+    abre TIME,
+    cierra TIME,
+    cerrado BOOLEAN NOT NULL DEFAULT FALSE,
+    CONSTRAINT chk_horario_horas CHECK (cerrado OR (abre IS NOT NULL AND cierra IS NOT NULL))
+);
+
+-- Por defecto todos los días 24 h, como hasta ahora; el staff lo ajusta
+INSERT INTO horarios_atencion (dia_semana, abre, cierra)
+SELECT d, '00:00', '00:00' FROM generate_series(1, 7) AS d;
+
+-- Excepciones por fecha (feriados cerrados, eventos privados, horario especial):
+-- reemplazan el horario semanal de ese día
+CREATE TABLE excepciones_horario (
+    fecha DATE PRIMARY KEY,
+    cerrado BOOLEAN NOT NULL DEFAULT TRUE,
+    abre TIME,
+    cierra TIME,
+    motivo VARCHAR(150),
+    CONSTRAINT chk_excepcion_horas CHECK (cerrado OR (abre IS NOT NULL AND cierra IS NOT NULL))
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE cabanas DROP COLUMN IF EXISTS minutos_limpieza;
+DROP TABLE IF EXISTS configuracion;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- =========================================
+-- ⚙️ Configuración general + margen de limpieza
+-- =========================================
+-- Una sola fila con los parámetros globales del negocio
+CREATE TABLE configuracion (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    -- Minutos libres entre dos reservas seguidas de una misma cabaña
+    minutos_limpieza INT NOT NULL DEFAULT 15 CHECK (minutos_limpieza >= 0)
+);
+
+INSERT INTO configuracion DEFAULT VALUES;
+
+-- NULL = usar configuracion.minutos_limpieza
+ALTER TABLE cabanas
+ADD COLUMN minutos_limpieza INT CHECK (minutos_limpieza >= 0);
@@ -0,0 +1,38 @@
+-- Your SQL goes here
+-- =========================================
+-- 📏 Reglas de reserva
+-- =========================================
+-- Cada fila fija algunos límites (los NULL no opinan). Alcance:
+-- - cabana_id NULL = todas las cabañas
+-- - dias_semana NULL = todos los días (1 = lunes ... 7 = domingo, de fecha_reserva)
+-- Para cada límite vale el de la regla más específica que lo defina:
+-- cabaña + días > cabaña > días > general.
+--
+-- Límites (en minutos salvo anticipacion_maxima_dias):
+-- - duracion_minima / duracion_maxima
+-- - granularidad: la hora de inicio debe caer en múltiplos (30 = :00 y :30)
+-- - anticipacion_minima: cuánto antes de empezar hay que reservar
+-- - anticipacion_maxima_dias: hasta cuántos días hacia adelante
+
+CREATE TABLE reglas_reserva (
+    id SERIAL PRIMARY KEY,
+    cabana_id INT REFERENCES cabanas(id) ON DELETE CASCADE,
+    dias_semana INT[],
+    duracion_minima INT CHECK (duracion_minima > 0),
+    duracion_maxima INT CHECK (duracion_maxima > 0),
+    granularidad INT CHECK (granularidad > 0 AND granularidad <= 60 AND 60 % granularidad = 0),
+    anticipacion_minima INT CHECK (anticipacion_minima >= 0),
+    anticipacion_maxima_dias INT CHECK (anticipacion_maxima_dias > 0),
+    CONSTRAINT chk_regla_reserva_duracion CHECK (duracion_minima IS NULL OR duracion_maxima IS NULL OR duracion_minima <= duracion_maxima),
+    CONSTRAINT chk_regla_reserva_dias CHECK (dias_semana IS NULL OR dias_semana <@ ARRAY[1,2,3,4,5,6,7])
+);
+
+CREATE INDEX idx_reglas_reserva_cabana ON reglas_reserva (cabana_id);
+
+-- Política actual del local
+INSERT INTO reglas_reserva (duracion_maxima, granularidad, anticipacion_minima, anticipacion_maxima_dias)
+VALUES (360, 30, 60, 60);
+-- Fin de semana = noches de viernes y sábado: el día se toma de fecha_reserva,
+-- así que un turno de sábado 23:00–02:00 cuenta como sábado
+INSERT INTO reglas_reserva (dias_semana, duracion_minima)
+VALUES (ARRAY[5,6], 120);
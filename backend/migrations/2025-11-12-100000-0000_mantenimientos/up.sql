@@ -0,0 +1,28 @@
+-- Your SQL goes here
+-- =========================================
+-- 🛠️ Ventanas de mantenimiento por cabaña
+-- =========================================
+-- Reemplazan al estado 'mantenimiento' puesto a mano, que no tenía rango y
+-- el actualizador automático pisaba. Durante [inicio, fin) la cabaña no se
+-- ofrece ni se puede reservar; el estado 'mantenimiento' pasa a reflejar
+-- que hay una ventana en curso.
+
+CREATE TABLE mantenimientos (
+    id SERIAL PRIMARY KEY,
+    cabana_id INT NOT NULL REFERENCES cabanas(id) ON DELETE CASCADE,
+    inicio TIMESTAMP NOT NULL,
+    fin TIMESTAMP NOT NULL,
+    motivo VARCHAR(200) NOT NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_mantenimiento_rango CHECK (fin > inicio)
+);
+
+CREATE INDEX idx_mantenimientos_cabana_rango ON mantenimientos (cabana_id, inicio, fin);
+
+-- Las cabañas que hoy están en mantenimiento conservan el bloqueo durante
+-- 30 días; el encargado ajusta después la fecha de fin real
+INSERT INTO mantenimientos (cabana_id, inicio, fin, motivo)
+SELECT id, date_trunc('minute', NOW()), date_trunc('minute', NOW()) + INTERVAL '30 days',
+       'Migrado del estado de la cabaña (revisar fecha de fin)'
+FROM cabanas
+WHERE estado = 'mantenimiento';
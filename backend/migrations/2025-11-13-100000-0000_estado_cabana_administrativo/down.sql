@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE cabanas ALTER COLUMN estado DROP DEFAULT;
+ALTER TYPE estado_cabana RENAME TO estado_cabana_administrativo;
+CREATE TYPE estado_cabana AS ENUM ('disponible', 'ocupada', 'mantenimiento');
+
+ALTER TABLE cabanas ALTER COLUMN estado TYPE estado_cabana
+    USING (CASE WHEN estado = 'activa' THEN 'disponible' ELSE 'mantenimiento' END)::estado_cabana;
+ALTER TABLE cabanas ALTER COLUMN estado SET DEFAULT 'disponible';
+
+DROP TYPE estado_cabana_administrativo;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- =========================================
+-- 🏷️ Estado de cabaña solo administrativo
+-- =========================================
+-- 'disponible' / 'ocupada' se guardaban al crear o borrar reservas y el
+-- actualizador los pisaba cada pocos segundos: casi siempre estaban mal.
+-- La ocupación ahora se calcula al leer (reservas + mantenimientos), y el
+-- estado guardado solo dice si la cabaña opera:
+-- - activa: se ofrece y se puede reservar
+-- - fuera de servicio: no se ofrece ni admite reservas nuevas
+-- El antiguo 'mantenimiento' ya se migró a ventanas en `mantenimientos`.
+
+ALTER TABLE cabanas ALTER COLUMN estado DROP DEFAULT;
+ALTER TYPE estado_cabana RENAME TO estado_cabana_anterior;
+CREATE TYPE estado_cabana AS ENUM ('activa', 'fuera de servicio');
+
+ALTER TABLE cabanas ALTER COLUMN estado TYPE estado_cabana USING 'activa'::estado_cabana;
+ALTER TABLE cabanas ALTER COLUMN estado SET DEFAULT 'activa';
+
+DROP TYPE estado_cabana_anterior;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservas DROP COLUMN IF EXISTS serie_id;
+DROP TABLE IF EXISTS series_reserva;
+DROP TYPE IF EXISTS frecuencia_serie;
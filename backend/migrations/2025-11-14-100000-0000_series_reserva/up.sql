@@ -0,0 +1,32 @@
+-- Your SQL goes here
+-- =========================================
+-- 🔁 Reservas recurrentes (series)
+-- =========================================
+-- Patrón al estilo RRULE: cada `intervalo` semanas (mismo día de la semana
+-- que fecha_inicio) o meses (mismo día del mes; los meses sin ese día se
+-- saltean), hasta una fecha o una cantidad de repeticiones.
+-- Cada ocurrencia es una reserva normal enlazada por `serie_id`; la serie
+-- guarda el patrón con el que se generaron.
+
+CREATE TYPE frecuencia_serie AS ENUM ('semanal', 'mensual');
+
+CREATE TABLE series_reserva (
+    id SERIAL PRIMARY KEY,
+    cliente_id INT NOT NULL REFERENCES clientes(id) ON DELETE RESTRICT,
+    cabana_id INT NOT NULL REFERENCES cabanas(id) ON DELETE RESTRICT,
+    frecuencia frecuencia_serie NOT NULL,
+    intervalo INT NOT NULL DEFAULT 1 CHECK (intervalo > 0),
+    fecha_inicio DATE NOT NULL,
+    hasta DATE,
+    repeticiones INT CHECK (repeticiones > 0),
+    hora_inicio TIME NOT NULL,
+    hora_fin TIME NOT NULL,
+    num_personas INT NOT NULL,
+    observaciones TEXT,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_serie_fin CHECK ((hasta IS NULL) <> (repeticiones IS NULL)),
+    CONSTRAINT chk_serie_hasta CHECK (hasta IS NULL OR hasta >= fecha_inicio)
+);
+
+ALTER TABLE reservas ADD COLUMN serie_id INT REFERENCES series_reserva(id) ON DELETE SET NULL;
+CREATE INDEX idx_reservas_serie ON reservas (serie_id) WHERE serie_id IS NOT NULL;
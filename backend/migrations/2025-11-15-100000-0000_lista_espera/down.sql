@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE configuracion DROP COLUMN IF EXISTS minutos_oferta_espera;
+DROP TABLE IF EXISTS ofertas_espera;
+DROP TABLE IF EXISTS lista_espera;
+DROP TYPE IF EXISTS estado_oferta;
+DROP TYPE IF EXISTS estado_espera;
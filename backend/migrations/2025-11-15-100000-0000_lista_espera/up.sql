@@ -0,0 +1,63 @@
+-- Your SQL goes here
+-- =========================================
+-- ⏳ Lista de espera y ofertas
+-- =========================================
+-- Un cliente que no consiguió lugar deja el horario que quería. Cuando se
+-- cancela o borra una reserva que se solapa con ese horario, las entradas
+-- en espera se evalúan por orden de llegada y a la primera que entra en la
+-- cabaña liberada se le crea una oferta con fecha de vencimiento.
+--
+-- Entradas:
+-- - esperando: sin oferta vigente
+-- - ofertada: tiene una oferta pendiente
+-- - atendida: aceptó una oferta (ya tiene su reserva)
+-- - cancelada: el cliente dejó de esperar
+--
+-- Ofertas:
+-- - pendiente → aceptada / rechazada / vencida
+-- Una oferta rechazada o vencida devuelve la entrada a 'esperando' y el
+-- hueco pasa a la siguiente de la lista.
+
+CREATE TYPE estado_espera AS ENUM ('esperando', 'ofertada', 'atendida', 'cancelada');
+CREATE TYPE estado_oferta AS ENUM ('pendiente', 'aceptada', 'rechazada', 'vencida');
+
+CREATE TABLE lista_espera (
+    id SERIAL PRIMARY KEY,
+    cliente_id INT NOT NULL REFERENCES clientes(id) ON DELETE RESTRICT,
+    fecha DATE NOT NULL,
+    hora_inicio TIME NOT NULL,
+    hora_fin TIME NOT NULL,
+    inicio TIMESTAMP NOT NULL GENERATED ALWAYS AS (fecha + hora_inicio) STORED,
+    fin TIMESTAMP NOT NULL
+        GENERATED ALWAYS AS (
+            fecha + hora_fin
+            + CASE WHEN hora_fin <= hora_inicio THEN INTERVAL '1 day' ELSE INTERVAL '0' END
+        ) STORED,
+    num_personas INT NOT NULL CHECK (num_personas > 0),
+    -- NULL = cualquier cabaña donde entre el grupo
+    cabana_ids INT[],
+    observaciones TEXT,
+    estado estado_espera NOT NULL DEFAULT 'esperando',
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_espera_horas CHECK (hora_fin <> hora_inicio)
+);
+
+CREATE INDEX idx_lista_espera_estado_inicio ON lista_espera (estado, inicio, fin);
+
+CREATE TABLE ofertas_espera (
+    id SERIAL PRIMARY KEY,
+    espera_id INT NOT NULL REFERENCES lista_espera(id) ON DELETE CASCADE,
+    cabana_id INT NOT NULL REFERENCES cabanas(id) ON DELETE CASCADE,
+    estado estado_oferta NOT NULL DEFAULT 'pendiente',
+    vence TIMESTAMP NOT NULL,
+    -- Reserva creada al aceptar
+    reserva_id INT REFERENCES reservas(id) ON DELETE SET NULL,
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Una sola oferta pendiente por entrada
+CREATE UNIQUE INDEX idx_ofertas_espera_pendiente ON ofertas_espera (espera_id) WHERE estado = 'pendiente';
+
+-- Minutos que tiene el cliente para aceptar una oferta
+ALTER TABLE configuracion
+ADD COLUMN minutos_oferta_espera INT NOT NULL DEFAULT 30 CHECK (minutos_oferta_espera > 0);
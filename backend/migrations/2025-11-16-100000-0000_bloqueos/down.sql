@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE configuracion DROP COLUMN IF EXISTS minutos_bloqueo;
+DROP TABLE IF EXISTS bloqueos;
@@ -0,0 +1,39 @@
+-- Your SQL goes here
+-- =========================================
+-- ⏱️ Bloqueos temporales
+-- =========================================
+-- Mientras el anfitrión toma los datos del cliente por teléfono, el horario
+-- queda retenido para que nadie más lo reserve. Hasta `vence` el bloqueo
+-- cuenta como ocupado en los chequeos de conflicto y en la disponibilidad;
+-- después se confirma como reserva, se libera a mano o lo borra el
+-- actualizador en segundo plano.
+
+CREATE TABLE bloqueos (
+    id SERIAL PRIMARY KEY,
+    cabana_id INT NOT NULL REFERENCES cabanas(id) ON DELETE CASCADE,
+    fecha DATE NOT NULL,
+    hora_inicio TIME NOT NULL,
+    hora_fin TIME NOT NULL,
+    inicio TIMESTAMP NOT NULL GENERATED ALWAYS AS (fecha + hora_inicio) STORED,
+    fin TIMESTAMP NOT NULL
+        GENERATED ALWAYS AS (
+            fecha + hora_fin
+            + CASE WHEN hora_fin <= hora_inicio THEN INTERVAL '1 day' ELSE INTERVAL '0' END
+        ) STORED,
+    vence TIMESTAMP NOT NULL,
+    -- Quién lo tomó o para quién (texto libre)
+    nota VARCHAR(200),
+    fecha_creacion TIMESTAMP NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_bloqueo_horas CHECK (hora_fin <> hora_inicio),
+    -- Dos anfitriones no pueden bloquear a la vez el mismo horario
+    CONSTRAINT bloqueos_sin_solapes EXCLUDE USING gist (
+        cabana_id WITH =,
+        tsrange(inicio, fin, '[)') WITH &&
+    )
+);
+
+CREATE INDEX idx_bloqueos_vence ON bloqueos (vence);
+
+-- Duración de un bloqueo
+ALTER TABLE configuracion
+ADD COLUMN minutos_bloqueo INT NOT NULL DEFAULT 10 CHECK (minutos_bloqueo > 0);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE configuracion DROP COLUMN IF EXISTS minutos_tolerancia_no_show;
+ALTER TABLE clientes DROP COLUMN IF EXISTS no_shows;
+ALTER TABLE reservas DROP CONSTRAINT IF EXISTS chk_reserva_salida;
+ALTER TABLE reservas DROP COLUMN IF EXISTS salida;
+ALTER TABLE reservas DROP COLUMN IF EXISTS llegada;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- =========================================
+-- 🛎️ Check-in, check-out y no presentados
+-- =========================================
+-- Una reserva ya no pasa sola a 'en curso' al llegar la hora: hace falta el
+-- check-in del anfitrión. Si pasada la tolerancia nadie se presentó, el
+-- actualizador la marca 'no_show' y la cabaña queda libre para quien llegue
+-- sin reserva. Cada no presentado queda contado en el perfil del cliente.
+
+ALTER TABLE reservas
+ADD COLUMN llegada TIMESTAMP,
+ADD COLUMN salida TIMESTAMP,
+ADD CONSTRAINT chk_reserva_salida CHECK (salida IS NULL OR (llegada IS NOT NULL AND salida >= llegada));
+
+-- Las que el reloj ya puso en curso se toman como llegadas a horario
+UPDATE reservas SET llegada = inicio WHERE estado = 'en curso';
+
+ALTER TABLE clientes
+ADD COLUMN no_shows INT NOT NULL DEFAULT 0 CHECK (no_shows >= 0);
+
+UPDATE clientes c
+SET no_shows = (SELECT COUNT(*) FROM reservas r WHERE r.cliente_id = c.id AND r.estado = 'no_show');
+
+-- Minutos después del inicio en que una reserva sin check-in pasa a 'no_show'
+ALTER TABLE configuracion
+ADD COLUMN minutos_tolerancia_no_show INT NOT NULL DEFAULT 15 CHECK (minutos_tolerancia_no_show >= 0);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_cabanas_zona;
+ALTER TABLE cabanas DROP COLUMN IF EXISTS zona;
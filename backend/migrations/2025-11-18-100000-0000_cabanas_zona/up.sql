@@ -0,0 +1,24 @@
+-- Your SQL goes here
+-- =========================================
+-- 🗺️ Zona explícita de cada cabaña
+-- =========================================
+-- Las reglas de precio por zona se comparaban con el prefijo de
+-- `cabanas.ubicacion`, un texto libre: 'Terraza norte' caía en una regla
+-- 'Terraza' y un error de tipeo dejaba la cabaña sin su regla. Ahora cada
+-- cabaña declara su zona y las reglas la nombran tal cual.
+
+ALTER TABLE cabanas ADD COLUMN zona VARCHAR(100);
+
+-- Se conserva el precio actual: cada cabaña toma la zona más larga de las
+-- reglas existentes que era prefijo de su ubicación
+UPDATE cabanas c
+SET zona = (
+    SELECT r.zona
+    FROM reglas_precio r
+    WHERE r.zona IS NOT NULL
+      AND LOWER(COALESCE(c.ubicacion, '')) LIKE LOWER(r.zona) || '%'
+    ORDER BY LENGTH(r.zona) DESC
+    LIMIT 1
+);
+
+CREATE INDEX idx_cabanas_zona ON cabanas (zona);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE configuracion DROP COLUMN IF EXISTS tasa_iva;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- =========================================
+-- 🧾 Tasa de IVA configurable
+-- =========================================
+-- Porcentaje de IVA incluido en el precio de las reservas. Cada comprobante
+-- copia la tasa vigente al emitirse (`comprobantes.tasa_impuesto`), así que
+-- cambiarla no altera los ya emitidos.
+ALTER TABLE configuracion
+ADD COLUMN tasa_iva NUMERIC(5,2) NOT NULL DEFAULT 13 CHECK (tasa_iva >= 0 AND tasa_iva < 100);
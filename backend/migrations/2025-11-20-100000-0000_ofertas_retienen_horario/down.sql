@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM bloqueos WHERE oferta_id IS NOT NULL;
+ALTER TABLE bloqueos DROP COLUMN IF EXISTS oferta_id;
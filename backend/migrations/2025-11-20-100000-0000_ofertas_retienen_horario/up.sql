@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- =========================================
+-- 📨 Las ofertas de la lista de espera retienen el horario
+-- =========================================
+-- Mientras una oferta está pendiente, su horario queda tomado con un bloqueo
+-- que vence junto con ella: así nadie reserva la cabaña por otra vía mientras
+-- el cliente decide. El bloqueo se borra al aceptar (justo antes de crear la
+-- reserva), rechazar, cancelar la entrada o vencer la oferta; no se puede
+-- confirmar ni liberar desde /bloqueos.
+
+ALTER TABLE bloqueos
+ADD COLUMN oferta_id INT UNIQUE REFERENCES ofertas_espera(id) ON DELETE CASCADE;
+
+-- Las ofertas pendientes de antes pasan a retener su horario, salvo que otro
+-- bloqueo ya lo tome
+INSERT INTO bloqueos (cabana_id, fecha, hora_inicio, hora_fin, vence, nota, oferta_id)
+SELECT o.cabana_id, e.fecha, e.hora_inicio, e.hora_fin, o.vence, 'Oferta de lista de espera #' || o.id, o.id
+FROM ofertas_espera o
+JOIN lista_espera e ON e.id = o.espera_id
+WHERE o.estado = 'pendiente'
+  AND o.vence > NOW()
+  AND NOT EXISTS (
+      SELECT 1 FROM bloqueos b
+      WHERE b.cabana_id = o.cabana_id
+        AND tsrange(b.inicio, b.fin, '[)') && tsrange(e.inicio, e.fin, '[)')
+  );
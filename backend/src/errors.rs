@@ -0,0 +1,195 @@
+use diesel::r2d2::PoolError;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Resultado estándar de los servicios y handlers de la API
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Mensaje común para solapamientos, detectados por el backend o por la BD
+pub const MENSAJE_CONFLICTO_HORARIO: &str = "⚠️ Conflicto de horario: ya existe una reserva en ese rango.";
+
+/// ❌ Errores de la API con su código HTTP asociado
+#[derive(Debug)]
+pub enum ApiError {
+    /// 404: el recurso solicitado no existe
+    NoEncontrado(String),
+    /// 409: ya existe una reserva que se solapa con el rango pedido
+    ConflictoHorario { mensaje: String, detalles: Option<Value> },
+    /// 409: el cambio choca con el estado actual de los datos
+    Conflicto { mensaje: String, detalles: Option<Value> },
+    /// 422: los datos enviados no son válidos
+    Validacion { mensaje: String, detalles: Option<Value> },
+    /// 503: no hay conexiones libres en el pool
+    ServicioNoDisponible(String),
+    /// 500: cualquier otro error inesperado
+    Interno(String),
+}
+
+/// 📦 Cuerpo JSON uniforme para todas las respuestas de error
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn no_encontrado(mensaje: impl Into<String>) -> Self {
+        ApiError::NoEncontrado(mensaje.into())
+    }
+
+    pub fn conflicto_horario(mensaje: impl Into<String>) -> Self {
+        ApiError::ConflictoHorario { mensaje: mensaje.into(), detalles: None }
+    }
+
+    pub fn conflicto(mensaje: impl Into<String>) -> Self {
+        ApiError::Conflicto { mensaje: mensaje.into(), detalles: None }
+    }
+
+    pub fn validacion(mensaje: impl Into<String>) -> Self {
+        ApiError::Validacion { mensaje: mensaje.into(), detalles: None }
+    }
+
+    /// Adjunta información adicional al campo `details` del cuerpo de error
+    pub fn con_detalles(self, valor: Value) -> Self {
+        match self {
+            ApiError::ConflictoHorario { mensaje, .. } => {
+                ApiError::ConflictoHorario { mensaje, detalles: Some(valor) }
+            }
+            ApiError::Conflicto { mensaje, .. } => ApiError::Conflicto { mensaje, detalles: Some(valor) },
+            ApiError::Validacion { mensaje, .. } => ApiError::Validacion { mensaje, detalles: Some(valor) },
+            otro => otro,
+        }
+    }
+
+    /// El pedido no se puede cumplir tal como está (409/422), a diferencia
+    /// de un recurso inexistente o un fallo interno
+    pub fn es_rechazo(&self) -> bool {
+        matches!(
+            self,
+            ApiError::ConflictoHorario { .. } | ApiError::Conflicto { .. } | ApiError::Validacion { .. }
+        )
+    }
+
+    pub fn status(&self) -> Status {
+        match self {
+            ApiError::NoEncontrado(_) => Status::NotFound,
+            ApiError::ConflictoHorario { .. } | ApiError::Conflicto { .. } => Status::Conflict,
+            ApiError::Validacion { .. } => Status::UnprocessableEntity,
+            ApiError::ServicioNoDisponible(_) => Status::ServiceUnavailable,
+            ApiError::Interno(_) => Status::InternalServerError,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NoEncontrado(_) => "no_encontrado",
+            ApiError::ConflictoHorario { .. } => "conflicto_horario",
+            ApiError::Conflicto { .. } => "conflicto",
+            ApiError::Validacion { .. } => "validacion",
+            ApiError::ServicioNoDisponible(_) => "servicio_no_disponible",
+            ApiError::Interno(_) => "error_interno",
+        }
+    }
+
+    pub fn into_body(self) -> ErrorBody {
+        let code = self.code();
+        let (message, details) = match self {
+            ApiError::NoEncontrado(m) | ApiError::ServicioNoDisponible(m) | ApiError::Interno(m) => (m, None),
+            ApiError::ConflictoHorario { mensaje, detalles }
+            | ApiError::Conflicto { mensaje, detalles }
+            | ApiError::Validacion { mensaje, detalles } => (mensaje, detalles),
+        };
+        ErrorBody { code, message, details }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::NoEncontrado(m) | ApiError::ServicioNoDisponible(m) | ApiError::Interno(m) => {
+                write!(f, "{}", m)
+            }
+            ApiError::ConflictoHorario { mensaje, .. }
+            | ApiError::Conflicto { mensaje, .. }
+            | ApiError::Validacion { mensaje, .. } => write!(f, "{}", mensaje),
+        }
+    }
+}
+
+// =============================
+// 🔁 Conversión desde Diesel / r2d2
+// =============================
+impl From<DieselError> for ApiError {
+    fn from(err: DieselError) -> Self {
+        match err {
+            DieselError::NotFound => ApiError::no_encontrado("El recurso solicitado no existe"),
+            DieselError::DatabaseError(kind, info) => {
+                let detalles = serde_json::json!({
+                    "constraint": info.constraint_name(),
+                    "table": info.table_name(),
+                    "column": info.column_name(),
+                    "detail": info.details(),
+                });
+                let mensaje = info.message().to_string();
+                match kind {
+                    // Restricción EXCLUDE de reservas: dos reservas solapadas en la misma cabaña
+                    DatabaseErrorKind::ExclusionViolation => {
+                        ApiError::conflicto_horario(MENSAJE_CONFLICTO_HORARIO).con_detalles(detalles)
+                    }
+                    DatabaseErrorKind::UniqueViolation
+                    | DatabaseErrorKind::ForeignKeyViolation
+                    | DatabaseErrorKind::RestrictViolation => {
+                        ApiError::conflicto(mensaje).con_detalles(detalles)
+                    }
+                    DatabaseErrorKind::CheckViolation | DatabaseErrorKind::NotNullViolation => {
+                        ApiError::validacion(mensaje).con_detalles(detalles)
+                    }
+                    _ => ApiError::Interno(format!("Error de base de datos: {}", mensaje)),
+                }
+            }
+            otro => ApiError::Interno(format!("Error de base de datos: {}", otro)),
+        }
+    }
+}
+
+impl From<PoolError> for ApiError {
+    fn from(err: PoolError) -> Self {
+        ApiError::ServicioNoDisponible(format!("No se pudo obtener conexión del pool: {}", err))
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = self.status();
+        if status == Status::InternalServerError {
+            eprintln!("❌ {} {}: {}", req.method(), req.uri(), self);
+        }
+        (status, Json(self.into_body())).respond_to(req)
+    }
+}
+
+/// 🪤 Catcher por defecto: las respuestas de error generadas por Rocket
+/// (rutas inexistentes, JSON mal formado, etc.) usan el mismo cuerpo JSON
+#[catch(default)]
+pub fn catcher_por_defecto(status: Status, _req: &Request) -> (Status, Json<ErrorBody>) {
+    let code = match status.code {
+        400 => "solicitud_invalida",
+        404 => "no_encontrado",
+        422 => "validacion",
+        503 => "servicio_no_disponible",
+        _ if status.code >= 500 => "error_interno",
+        _ => "error",
+    };
+    let body = ErrorBody {
+        code,
+        message: status.reason_lossy().to_string(),
+        details: None,
+    };
+    (status, Json(body))
+}
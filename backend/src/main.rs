@@ -9,12 +9,15 @@ use std::time::Duration;
 mod schema;
 mod models;
 mod db;
+mod errors;
+mod pdf;
 mod services;
 mod websocket;
 
 use db::DbPool;
-use models::{Reserva, NewReserva, Cliente, NewCliente, Cabana, NewCabana};
-use services::{reservas_service, clientes_service, cabanas_service};
+use errors::{ApiError, ApiResult};
+use models::{Cotizacion, DiaTarifas, DisponibilidadCabana, EstadoCabana, EstadoReserva, FiltroDisponibilidad, FiltroReservas, Pagina, Reserva, NewReserva, UpdateReserva, Cliente, NewCliente, UpdateCliente, Cabana, CabanaConOcupacion, NewCabana, UpdateCabana, SolicitudCotizacion, ReglaPrecio, NewReglaPrecio, UpdateReglaPrecio, Feriado, EstadoCuenta, Pago, SolicitudPago, TipoPago, Promocion, NewPromocion, UpdatePromocion, HorarioAtencion, HorarioDia, ExcepcionHorario, Configuracion, UpdateConfiguracion, ReglaReserva, NewReglaReserva, UpdateReglaReserva, LimitesReserva, FiltroLimites, Mantenimiento, NewMantenimiento, UpdateMantenimiento, DetalleMantenimiento, FiltroMantenimientos, NewSerieReserva, DetalleSerie, CambiosSerie, AlcanceSerie, EntradaEspera, NewEntradaEspera, OfertaEspera, FiltroListaEspera, Bloqueo, NewBloqueo, ConfirmarBloqueo};
+use services::{reservas_service, bloqueos_service, clientes_service, cabanas_service, comprobantes_service, configuracion_service, disponibilidad_service, horarios_service, lista_espera_service, mantenimientos_service, pagos_service, precios_service, promociones_service, reglas_precio_service, reglas_reserva_service, series_service, validaciones_service};
 use websocket::{Broadcaster, ws};
 
 // =========================
@@ -29,28 +32,76 @@ fn index() -> &'static str {
 // 🧍 CLIENTES
 // =========================
 #[get("/clientes")]
-fn listar_clientes(pool: &State<DbPool>) -> Json<Vec<Cliente>> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let results = clientes_service::listar_clientes(&mut conn).expect("Error al listar clientes");
-    Json(results)
+fn listar_clientes(pool: &State<DbPool>) -> ApiResult<Json<Vec<Cliente>>> {
+    let mut conn = pool.get()?;
+    let results = clientes_service::listar_clientes(&mut conn)?;
+    Ok(Json(results))
 }
 
 #[post("/clientes", format = "json", data = "<nuevo_cliente>")]
-fn crear_cliente(pool: &State<DbPool>, nuevo_cliente: Json<NewCliente>) -> Json<Cliente> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let cliente =
-        clientes_service::crear_cliente(&mut conn, nuevo_cliente.into_inner()).expect("Error al crear cliente");
-    Json(cliente)
+fn crear_cliente(pool: &State<DbPool>, nuevo_cliente: Json<NewCliente>) -> ApiResult<Json<Cliente>> {
+    let mut conn = pool.get()?;
+    let cliente = clientes_service::crear_cliente(&mut conn, nuevo_cliente.into_inner())?;
+    Ok(Json(cliente))
+}
+
+#[get("/clientes/<id>")]
+fn obtener_cliente(pool: &State<DbPool>, id: i32) -> ApiResult<Json<Cliente>> {
+    let mut conn = pool.get()?;
+    let cliente = clientes_service::obtener_cliente(&mut conn, id)?;
+    Ok(Json(cliente))
+}
+
+/// Reemplaza el cliente completo: los campos opcionales ausentes se vacían
+#[put("/clientes/<id>", format = "json", data = "<datos>")]
+fn reemplazar_cliente(
+    pool: &State<DbPool>,
+    id: i32,
+    datos: Json<NewCliente>,
+) -> ApiResult<Json<Cliente>> {
+    let mut conn = pool.get()?;
+    let cliente = clientes_service::reemplazar_cliente(&mut conn, id, datos.into_inner())?;
+    Ok(Json(cliente))
+}
+
+#[patch("/clientes/<id>", format = "json", data = "<cambios>")]
+fn actualizar_cliente(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateCliente>,
+) -> ApiResult<Json<Cliente>> {
+    let mut conn = pool.get()?;
+    let cliente = clientes_service::actualizar_cliente(&mut conn, id, cambios.into_inner())?;
+    Ok(Json(cliente))
+}
+
+#[delete("/clientes/<id>")]
+fn eliminar_cliente(pool: &State<DbPool>, id: i32) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    clientes_service::eliminar_cliente(&mut conn, id)?;
+    Ok(Json(format!("🗑️ Cliente {} eliminado correctamente", id)))
 }
 
 // =========================
 // 🏠 CABAÑAS
 // =========================
-#[get("/cabanas")]
-fn listar_cabanas(pool: &State<DbPool>) -> Json<Vec<Cabana>> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let result = cabanas_service::listar_cabanas(&mut conn).expect("Error al listar cabañas");
-    Json(result)
+#[get("/cabanas?<incluir_archivadas>")]
+fn listar_cabanas(
+    pool: &State<DbPool>,
+    incluir_archivadas: Option<bool>,
+) -> ApiResult<Json<Vec<CabanaConOcupacion>>> {
+    let mut conn = pool.get()?;
+    let cabanas = cabanas_service::listar_cabanas(&mut conn, incluir_archivadas.unwrap_or(false))?;
+    let result = cabanas_service::con_ocupacion(&mut conn, cabanas)?;
+    Ok(Json(result))
+}
+
+#[get("/cabanas/<id>")]
+fn obtener_cabana(pool: &State<DbPool>, id: i32) -> ApiResult<Json<CabanaConOcupacion>> {
+    let mut conn = pool.get()?;
+    let cab = cabanas_service::obtener_cabana(&mut conn, id)?;
+    let mut result = cabanas_service::con_ocupacion(&mut conn, vec![cab])?;
+    Ok(Json(result.remove(0)))
 }
 
 #[post("/cabanas", format = "json", data = "<nueva_cabana>")]
@@ -58,12 +109,36 @@ fn crear_cabana(
     pool: &State<DbPool>,
     nueva_cabana: Json<NewCabana>,
     broadcaster: &State<Broadcaster>,
-) -> Json<Cabana> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let cab = cabanas_service::crear_cabana(&mut conn, nueva_cabana.into_inner())
-        .expect("Error al crear cabaña");
+) -> ApiResult<Json<Cabana>> {
+    let mut conn = pool.get()?;
+    let cab = cabanas_service::crear_cabana(&mut conn, nueva_cabana.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(cab))
+}
+
+#[patch("/cabanas/<id>", format = "json", data = "<cambios>")]
+fn actualizar_cabana(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateCabana>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Cabana>> {
+    let mut conn = pool.get()?;
+    let cab = cabanas_service::actualizar_cabana(&mut conn, id, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(cab))
+}
+
+#[post("/cabanas/<id>/archivar")]
+fn archivar_cabana(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Cabana>> {
+    let mut conn = pool.get()?;
+    let cab = cabanas_service::archivar_cabana(&mut conn, id)?;
     broadcaster.send("actualizar");
-    Json(cab)
+    Ok(Json(cab))
 }
 
 #[put("/cabanas/<cabana_id>/<nuevo_estado>")]
@@ -72,22 +147,358 @@ fn actualizar_estado_cabana(
     cabana_id: i32,
     nuevo_estado: &str,
     broadcaster: &State<Broadcaster>,
-) -> Json<Cabana> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let cab =
-        cabanas_service::actualizar_estado(&mut conn, cabana_id, nuevo_estado).expect("Error al actualizar estado");
+) -> ApiResult<Json<Cabana>> {
+    let mut conn = pool.get()?;
+    let nuevo_estado: EstadoCabana = nuevo_estado.parse()?;
+    let cab = cabanas_service::actualizar_estado(&mut conn, cabana_id, nuevo_estado)?;
+    broadcaster.send("actualizar");
+    Ok(Json(cab))
+}
+
+// =========================
+// 🛠️ MANTENIMIENTOS
+// =========================
+#[get("/mantenimientos?<filtro..>")]
+fn listar_mantenimientos(
+    pool: &State<DbPool>,
+    filtro: FiltroMantenimientos,
+) -> ApiResult<Json<Vec<Mantenimiento>>> {
+    let mut conn = pool.get()?;
+    let results = mantenimientos_service::listar(&mut conn, filtro)?;
+    Ok(Json(results))
+}
+
+#[get("/mantenimientos/<id>")]
+fn obtener_mantenimiento(pool: &State<DbPool>, id: i32) -> ApiResult<Json<DetalleMantenimiento>> {
+    let mut conn = pool.get()?;
+    let detalle = mantenimientos_service::obtener(&mut conn, id)?;
+    Ok(Json(detalle))
+}
+
+/// Programa la ventana y devuelve las reservas que quedaron dentro para moverlas
+#[post("/mantenimientos", format = "json", data = "<nuevo>")]
+fn crear_mantenimiento(
+    pool: &State<DbPool>,
+    nuevo: Json<NewMantenimiento>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<DetalleMantenimiento>> {
+    let mut conn = pool.get()?;
+    let detalle = mantenimientos_service::crear(&mut conn, nuevo.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(detalle))
+}
+
+#[patch("/mantenimientos/<id>", format = "json", data = "<cambios>")]
+fn actualizar_mantenimiento(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateMantenimiento>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<DetalleMantenimiento>> {
+    let mut conn = pool.get()?;
+    let detalle = mantenimientos_service::actualizar(&mut conn, id, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(detalle))
+}
+
+#[delete("/mantenimientos/<id>")]
+fn eliminar_mantenimiento(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    mantenimientos_service::eliminar(&mut conn, id)?;
+    broadcaster.send("actualizar");
+    Ok(Json(format!("🗑️ Mantenimiento {} eliminado correctamente", id)))
+}
+
+// =========================
+// ⚙️ CONFIGURACIÓN
+// =========================
+#[get("/configuracion")]
+fn obtener_configuracion(pool: &State<DbPool>) -> ApiResult<Json<Configuracion>> {
+    let mut conn = pool.get()?;
+    let config = configuracion_service::obtener(&mut conn)?;
+    Ok(Json(config))
+}
+
+#[patch("/configuracion", format = "json", data = "<cambios>")]
+fn actualizar_configuracion(
+    pool: &State<DbPool>,
+    cambios: Json<UpdateConfiguracion>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Configuracion>> {
+    let mut conn = pool.get()?;
+    let config = configuracion_service::actualizar(&mut conn, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(config))
+}
+
+// =========================
+// 📏 REGLAS DE RESERVA
+// =========================
+#[get("/reglas-reserva?<cabana_id>")]
+fn listar_reglas_reserva(pool: &State<DbPool>, cabana_id: Option<i32>) -> ApiResult<Json<Vec<ReglaReserva>>> {
+    let mut conn = pool.get()?;
+    let results = reglas_reserva_service::listar_reglas(&mut conn, cabana_id)?;
+    Ok(Json(results))
+}
+
+/// Límites que se aplicarán a una reserva de esa cabaña y fecha (para el formulario)
+#[get("/reglas-reserva/efectivas?<filtro..>")]
+fn limites_reserva(pool: &State<DbPool>, filtro: FiltroLimites) -> ApiResult<Json<LimitesReserva>> {
+    let mut conn = pool.get()?;
+    let fecha = match filtro.fecha.as_deref() {
+        Some(v) => validaciones_service::parsear_fecha("fecha", v)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let limites = reglas_reserva_service::limites(&mut conn, filtro.cabana_id, fecha)?;
+    Ok(Json(limites))
+}
+
+#[post("/reglas-reserva", format = "json", data = "<nueva_regla>")]
+fn crear_regla_reserva(
+    pool: &State<DbPool>,
+    nueva_regla: Json<NewReglaReserva>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<ReglaReserva>> {
+    let mut conn = pool.get()?;
+    let regla = reglas_reserva_service::crear_regla(&mut conn, nueva_regla.into_inner())?;
     broadcaster.send("actualizar");
-    Json(cab)
+    Ok(Json(regla))
+}
+
+#[put("/reglas-reserva/<id>", format = "json", data = "<cambios>")]
+fn actualizar_regla_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateReglaReserva>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<ReglaReserva>> {
+    let mut conn = pool.get()?;
+    let regla = reglas_reserva_service::actualizar_regla(&mut conn, id, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(regla))
+}
+
+#[delete("/reglas-reserva/<id>")]
+fn eliminar_regla_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    reglas_reserva_service::eliminar_regla(&mut conn, id)?;
+    broadcaster.send("actualizar");
+    Ok(Json(format!("🗑️ Regla de reserva {} eliminada correctamente", id)))
+}
+
+// =========================
+// 🕘 HORARIO DE ATENCIÓN
+// =========================
+#[get("/horarios")]
+fn listar_horario(pool: &State<DbPool>) -> ApiResult<Json<Vec<HorarioAtencion>>> {
+    let mut conn = pool.get()?;
+    let results = horarios_service::listar_horario(&mut conn)?;
+    Ok(Json(results))
+}
+
+#[put("/horarios/<dia_semana>", format = "json", data = "<horario>")]
+fn actualizar_horario(
+    pool: &State<DbPool>,
+    dia_semana: i32,
+    horario: Json<HorarioDia>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<HorarioAtencion>> {
+    let mut conn = pool.get()?;
+    let result = horarios_service::actualizar_dia(&mut conn, dia_semana, horario.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(result))
+}
+
+#[get("/horarios/excepciones?<desde>")]
+fn listar_excepciones_horario(
+    pool: &State<DbPool>,
+    desde: Option<&str>,
+) -> ApiResult<Json<Vec<ExcepcionHorario>>> {
+    let mut conn = pool.get()?;
+    let desde = desde.map(|v| validaciones_service::parsear_fecha("desde", v)).transpose()?;
+    let results = horarios_service::listar_excepciones(&mut conn, desde)?;
+    Ok(Json(results))
+}
+
+#[post("/horarios/excepciones", format = "json", data = "<excepcion>")]
+fn guardar_excepcion_horario(
+    pool: &State<DbPool>,
+    excepcion: Json<ExcepcionHorario>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<ExcepcionHorario>> {
+    let mut conn = pool.get()?;
+    let result = horarios_service::guardar_excepcion(&mut conn, excepcion.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(result))
+}
+
+#[delete("/horarios/excepciones/<fecha>")]
+fn eliminar_excepcion_horario(
+    pool: &State<DbPool>,
+    fecha: &str,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    let fecha = validaciones_service::parsear_fecha("fecha", fecha)?;
+    horarios_service::eliminar_excepcion(&mut conn, fecha)?;
+    broadcaster.send("actualizar");
+    Ok(Json(format!("🗑️ Excepción de horario del {} eliminada correctamente", fecha)))
+}
+
+// =========================
+// 🟢 DISPONIBILIDAD
+// =========================
+#[get("/cabanas/<id>/disponibilidad?<fecha>")]
+fn disponibilidad_cabana(
+    pool: &State<DbPool>,
+    id: i32,
+    fecha: Option<&str>,
+) -> ApiResult<Json<DisponibilidadCabana>> {
+    let mut conn = pool.get()?;
+    let result = disponibilidad_service::disponibilidad_cabana(&mut conn, id, fecha)?;
+    Ok(Json(result))
+}
+
+#[get("/disponibilidad?<filtro..>")]
+fn buscar_disponibilidad(
+    pool: &State<DbPool>,
+    filtro: FiltroDisponibilidad,
+) -> ApiResult<Json<Vec<DisponibilidadCabana>>> {
+    let mut conn = pool.get()?;
+    let result = disponibilidad_service::buscar_disponibilidad(&mut conn, filtro)?;
+    Ok(Json(result))
+}
+
+// =========================
+// 📈 PRECIOS DINÁMICOS
+// =========================
+#[get("/reglas-precio?<cabana_id>")]
+fn listar_reglas_precio(pool: &State<DbPool>, cabana_id: Option<i32>) -> ApiResult<Json<Vec<ReglaPrecio>>> {
+    let mut conn = pool.get()?;
+    let results = reglas_precio_service::listar_reglas(&mut conn, cabana_id)?;
+    Ok(Json(results))
+}
+
+#[get("/reglas-precio/<id>")]
+fn obtener_regla_precio(pool: &State<DbPool>, id: i32) -> ApiResult<Json<ReglaPrecio>> {
+    let mut conn = pool.get()?;
+    let regla = reglas_precio_service::obtener_regla(&mut conn, id)?;
+    Ok(Json(regla))
+}
+
+#[post("/reglas-precio", format = "json", data = "<nueva_regla>")]
+fn crear_regla_precio(pool: &State<DbPool>, nueva_regla: Json<NewReglaPrecio>) -> ApiResult<Json<ReglaPrecio>> {
+    let mut conn = pool.get()?;
+    let regla = reglas_precio_service::crear_regla(&mut conn, nueva_regla.into_inner())?;
+    Ok(Json(regla))
+}
+
+#[patch("/reglas-precio/<id>", format = "json", data = "<cambios>")]
+fn actualizar_regla_precio(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateReglaPrecio>,
+) -> ApiResult<Json<ReglaPrecio>> {
+    let mut conn = pool.get()?;
+    let regla = reglas_precio_service::actualizar_regla(&mut conn, id, cambios.into_inner())?;
+    Ok(Json(regla))
+}
+
+#[delete("/reglas-precio/<id>")]
+fn eliminar_regla_precio(pool: &State<DbPool>, id: i32) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    reglas_precio_service::eliminar_regla(&mut conn, id)?;
+    Ok(Json(format!("🗑️ Regla de precio {} eliminada correctamente", id)))
+}
+
+#[get("/feriados")]
+fn listar_feriados(pool: &State<DbPool>) -> ApiResult<Json<Vec<Feriado>>> {
+    let mut conn = pool.get()?;
+    let results = reglas_precio_service::listar_feriados(&mut conn)?;
+    Ok(Json(results))
+}
+
+#[post("/feriados", format = "json", data = "<feriado>")]
+fn crear_feriado(pool: &State<DbPool>, feriado: Json<Feriado>) -> ApiResult<Json<Feriado>> {
+    let mut conn = pool.get()?;
+    let feriado = reglas_precio_service::crear_feriado(&mut conn, feriado.into_inner())?;
+    Ok(Json(feriado))
+}
+
+#[delete("/feriados/<fecha>")]
+fn eliminar_feriado(pool: &State<DbPool>, fecha: &str) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    let fecha = validaciones_service::parsear_fecha("fecha", fecha)?;
+    reglas_precio_service::eliminar_feriado(&mut conn, fecha)?;
+    Ok(Json(format!("🗑️ Feriado del {} eliminado correctamente", fecha)))
+}
+
+/// Vista previa de la tarifa efectiva de la cabaña durante una semana
+#[get("/cabanas/<id>/tarifas?<desde>")]
+fn tarifas_cabana(pool: &State<DbPool>, id: i32, desde: Option<&str>) -> ApiResult<Json<Vec<DiaTarifas>>> {
+    let mut conn = pool.get()?;
+    let grilla = precios_service::grilla_tarifas(&mut conn, id, desde)?;
+    Ok(Json(grilla))
+}
+
+// =========================
+// 🏷️ PROMOCIONES
+// =========================
+#[get("/promociones")]
+fn listar_promociones(pool: &State<DbPool>) -> ApiResult<Json<Vec<Promocion>>> {
+    let mut conn = pool.get()?;
+    let results = promociones_service::listar_promociones(&mut conn)?;
+    Ok(Json(results))
+}
+
+#[get("/promociones/<id>")]
+fn obtener_promocion(pool: &State<DbPool>, id: i32) -> ApiResult<Json<Promocion>> {
+    let mut conn = pool.get()?;
+    let promo = promociones_service::obtener_promocion(&mut conn, id)?;
+    Ok(Json(promo))
+}
+
+#[post("/promociones", format = "json", data = "<nueva_promocion>")]
+fn crear_promocion(pool: &State<DbPool>, nueva_promocion: Json<NewPromocion>) -> ApiResult<Json<Promocion>> {
+    let mut conn = pool.get()?;
+    let promo = promociones_service::crear_promocion(&mut conn, nueva_promocion.into_inner())?;
+    Ok(Json(promo))
+}
+
+#[patch("/promociones/<id>", format = "json", data = "<cambios>")]
+fn actualizar_promocion(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdatePromocion>,
+) -> ApiResult<Json<Promocion>> {
+    let mut conn = pool.get()?;
+    let promo = promociones_service::actualizar_promocion(&mut conn, id, cambios.into_inner())?;
+    Ok(Json(promo))
+}
+
+#[delete("/promociones/<id>")]
+fn eliminar_promocion(pool: &State<DbPool>, id: i32) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    promociones_service::eliminar_promocion(&mut conn, id)?;
+    Ok(Json(format!("🗑️ Promoción {} eliminada correctamente", id)))
 }
 
 // =========================
 // 📅 RESERVAS
 // =========================
-#[get("/reservas")]
-fn listar_reservas(pool: &State<DbPool>) -> Json<Vec<Reserva>> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    let results = reservas_service::listar_reservas(&mut conn).expect("Error al listar reservas");
-    Json(results)
+#[get("/reservas?<filtro..>")]
+fn listar_reservas(pool: &State<DbPool>, filtro: FiltroReservas) -> ApiResult<Json<Pagina<Reserva>>> {
+    let mut conn = pool.get()?;
+    let results = reservas_service::listar_reservas(&mut conn, filtro)?;
+    Ok(Json(results))
 }
 
 #[post("/reservas", format = "json", data = "<nueva_reserva>")]
@@ -95,21 +506,34 @@ fn crear_reserva(
     pool: &State<DbPool>,
     nueva_reserva: Json<NewReserva>,
     broadcaster: &State<Broadcaster>,
-) -> Result<Json<Reserva>, Json<serde_json::Value>> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-
-    match reservas_service::crear_reserva(&mut conn, nueva_reserva.into_inner()) {
-        Ok(reserva) => {
-            broadcaster.send("actualizar");
-            Ok(Json(reserva))
-        }
-        Err(diesel::result::Error::RollbackTransaction) => Err(Json(serde_json::json!({
-            "error": "⚠️ Conflicto de horario: ya existe una reserva en ese rango."
-        }))),
-        Err(e) => Err(Json(serde_json::json!({
-            "error": format!("❌ Error inesperado: {:?}", e)
-        }))),
-    }
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let reserva = reservas_service::crear_reserva(&mut conn, nueva_reserva.into_inner(), false)?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
+}
+
+#[post("/reservas/cotizar", format = "json", data = "<solicitud>")]
+fn cotizar_reserva(
+    pool: &State<DbPool>,
+    solicitud: Json<SolicitudCotizacion>,
+) -> ApiResult<Json<Cotizacion>> {
+    let mut conn = pool.get()?;
+    let cotizacion = precios_service::cotizar_solicitud(&mut conn, solicitud.into_inner())?;
+    Ok(Json(cotizacion))
+}
+
+#[patch("/reservas/<id>", format = "json", data = "<cambios>")]
+fn actualizar_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    cambios: Json<UpdateReserva>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let reserva = reservas_service::actualizar_reserva(&mut conn, id, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
 }
 
 #[delete("/reservas/<id>")]
@@ -117,11 +541,12 @@ fn eliminar_reserva(
     pool: &State<DbPool>,
     id: i32,
     broadcaster: &State<Broadcaster>,
-) -> Json<String> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    reservas_service::eliminar_reserva(&mut conn, id).expect("Error al eliminar reserva");
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    let ofertas = reservas_service::eliminar_reserva(&mut conn, id)?;
+    notificar_ofertas(broadcaster, &ofertas);
     broadcaster.send("actualizar");
-    Json(format!("🗑️ Reserva {} eliminada correctamente", id))
+    Ok(Json(format!("🗑️ Reserva {} eliminada correctamente", id)))
 }
 
 #[put("/reservas/<id>/<nuevo_estado>")]
@@ -130,19 +555,324 @@ fn actualizar_estado_reserva(
     id: i32,
     nuevo_estado: &str,
     broadcaster: &State<Broadcaster>,
-) -> Json<String> {
-    let mut conn = pool.get().expect("No se pudo obtener conexión del pool");
-    reservas_service::actualizar_estado_reserva(&mut conn, id, nuevo_estado)
-        .expect("Error al actualizar estado de reserva");
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    let nuevo_estado: EstadoReserva = nuevo_estado.parse()?;
+    let (_, ofertas) = reservas_service::actualizar_estado_reserva(&mut conn, id, nuevo_estado)?;
+    notificar_ofertas(broadcaster, &ofertas);
+    broadcaster.send("actualizar");
+    Ok(Json(format!("✅ Reserva {} marcada como {}", id, nuevo_estado)))
+}
+
+/// 🛎️ Registra la llegada: la reserva pasa a 'en curso'
+#[post("/reservas/<id>/check-in")]
+fn check_in_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let (reserva, _) = reservas_service::actualizar_estado_reserva(&mut conn, id, EstadoReserva::EnCurso)?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
+}
+
+/// 🧳 Registra la salida: la reserva pasa a 'completada'
+#[post("/reservas/<id>/check-out")]
+fn check_out_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let (reserva, _) = reservas_service::actualizar_estado_reserva(&mut conn, id, EstadoReserva::Completada)?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
+}
+
+// =========================
+// ⏱️ BLOQUEOS TEMPORALES
+// =========================
+#[get("/bloqueos")]
+fn listar_bloqueos(pool: &State<DbPool>) -> ApiResult<Json<Vec<Bloqueo>>> {
+    let mut conn = pool.get()?;
+    let bloqueos = bloqueos_service::listar(&mut conn)?;
+    Ok(Json(bloqueos))
+}
+
+/// Retiene el horario durante `configuracion.minutos_bloqueo`
+#[post("/bloqueos", format = "json", data = "<nuevo_bloqueo>")]
+fn crear_bloqueo(
+    pool: &State<DbPool>,
+    nuevo_bloqueo: Json<NewBloqueo>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Bloqueo>> {
+    let mut conn = pool.get()?;
+    let bloqueo = bloqueos_service::crear(&mut conn, nuevo_bloqueo.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(bloqueo))
+}
+
+#[post("/bloqueos/<id>/confirmar", format = "json", data = "<datos>")]
+fn confirmar_bloqueo(
+    pool: &State<DbPool>,
+    id: i32,
+    datos: Json<ConfirmarBloqueo>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let reserva = bloqueos_service::confirmar(&mut conn, id, datos.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
+}
+
+#[delete("/bloqueos/<id>")]
+fn liberar_bloqueo(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<String>> {
+    let mut conn = pool.get()?;
+    let ofertas = bloqueos_service::liberar(&mut conn, id)?;
+    notificar_ofertas(broadcaster, &ofertas);
+    broadcaster.send("actualizar");
+    Ok(Json(format!("🔓 Bloqueo {} liberado", id)))
+}
+
+// =========================
+// 🔁 SERIES DE RESERVAS
+// =========================
+/// Crea todas las ocurrencias; si alguna fecha falla responde 409 con la
+/// lista, salvo que se pida `omitir_conflictos`
+#[post("/series", format = "json", data = "<nueva_serie>")]
+fn crear_serie(
+    pool: &State<DbPool>,
+    nueva_serie: Json<NewSerieReserva>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<DetalleSerie>> {
+    let mut conn = pool.get()?;
+    let serie = series_service::crear_serie(&mut conn, nueva_serie.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(serie))
+}
+
+#[get("/series/<id>")]
+fn obtener_serie(pool: &State<DbPool>, id: i32) -> ApiResult<Json<DetalleSerie>> {
+    let mut conn = pool.get()?;
+    let serie = series_service::obtener_serie(&mut conn, id)?;
+    Ok(Json(serie))
+}
+
+#[patch("/series/<id>?<alcance..>", format = "json", data = "<cambios>")]
+fn actualizar_serie(
+    pool: &State<DbPool>,
+    id: i32,
+    alcance: AlcanceSerie,
+    cambios: Json<CambiosSerie>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<DetalleSerie>> {
+    let mut conn = pool.get()?;
+    let serie = series_service::actualizar_serie(&mut conn, id, alcance, cambios.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(serie))
+}
+
+#[post("/series/<id>/cancelar?<alcance..>")]
+fn cancelar_serie(
+    pool: &State<DbPool>,
+    id: i32,
+    alcance: AlcanceSerie,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<DetalleSerie>> {
+    let mut conn = pool.get()?;
+    let (serie, ofertas) = series_service::cancelar_serie(&mut conn, id, alcance)?;
+    notificar_ofertas(broadcaster, &ofertas);
     broadcaster.send("actualizar");
-    Json(format!("✅ Reserva {} marcada como {}", id, nuevo_estado))
+    Ok(Json(serie))
+}
+
+// =========================
+// ⏳ LISTA DE ESPERA
+// =========================
+/// Por defecto solo las entradas abiertas (esperando u ofertadas)
+#[get("/lista-espera?<filtro..>")]
+fn listar_lista_espera(pool: &State<DbPool>, filtro: FiltroListaEspera) -> ApiResult<Json<Vec<EntradaEspera>>> {
+    let mut conn = pool.get()?;
+    let entradas = lista_espera_service::listar(&mut conn, filtro)?;
+    Ok(Json(entradas))
+}
+
+#[post("/lista-espera", format = "json", data = "<nueva_entrada>")]
+fn crear_entrada_espera(
+    pool: &State<DbPool>,
+    nueva_entrada: Json<NewEntradaEspera>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<EntradaEspera>> {
+    let mut conn = pool.get()?;
+    let entrada = lista_espera_service::crear(&mut conn, nueva_entrada.into_inner())?;
+    broadcaster.send("actualizar");
+    Ok(Json(entrada))
+}
+
+#[delete("/lista-espera/<id>")]
+fn cancelar_entrada_espera(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<EntradaEspera>> {
+    let mut conn = pool.get()?;
+    let (entrada, ofertas) = lista_espera_service::cancelar(&mut conn, id)?;
+    notificar_ofertas(broadcaster, &ofertas);
+    broadcaster.send("actualizar");
+    Ok(Json(entrada))
+}
+
+/// Por defecto solo las pendientes, de la que vence antes a la última
+#[get("/lista-espera/ofertas?<filtro..>")]
+fn listar_ofertas_espera(pool: &State<DbPool>, filtro: FiltroListaEspera) -> ApiResult<Json<Vec<OfertaEspera>>> {
+    let mut conn = pool.get()?;
+    let ofertas = lista_espera_service::listar_ofertas(&mut conn, filtro)?;
+    Ok(Json(ofertas))
+}
+
+#[post("/lista-espera/ofertas/<id>/aceptar")]
+fn aceptar_oferta_espera(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Reserva>> {
+    let mut conn = pool.get()?;
+    let reserva = lista_espera_service::aceptar_oferta(&mut conn, id)?;
+    broadcaster.send("actualizar");
+    Ok(Json(reserva))
+}
+
+/// Devuelve las ofertas que recibieron las siguientes entradas de la lista
+#[post("/lista-espera/ofertas/<id>/rechazar")]
+fn rechazar_oferta_espera(
+    pool: &State<DbPool>,
+    id: i32,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Vec<OfertaEspera>>> {
+    let mut conn = pool.get()?;
+    let ofertas = lista_espera_service::rechazar_oferta(&mut conn, id)?;
+    notificar_ofertas(broadcaster, &ofertas);
+    broadcaster.send("actualizar");
+    Ok(Json(ofertas))
+}
+
+/// 📡 Avisa al personal de cada cabaña liberada que se ofreció a la lista de espera
+fn notificar_ofertas(broadcaster: &Broadcaster, ofertas: &[OfertaEspera]) {
+    for oferta in ofertas {
+        broadcaster.send_evento("oferta_lista_espera", oferta);
+    }
+}
+
+// =========================
+// 💵 PAGOS
+// =========================
+#[get("/reservas/<id>/pagos")]
+fn listar_pagos(pool: &State<DbPool>, id: i32) -> ApiResult<Json<EstadoCuenta>> {
+    let mut conn = pool.get()?;
+    let cuenta = pagos_service::estado_cuenta(&mut conn, id)?;
+    Ok(Json(cuenta))
+}
+
+#[post("/reservas/<id>/pagos", format = "json", data = "<solicitud>")]
+fn registrar_pago(
+    pool: &State<DbPool>,
+    id: i32,
+    solicitud: Json<SolicitudPago>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Pago>> {
+    let mut conn = pool.get()?;
+    let (pago, reserva) =
+        pagos_service::registrar_movimiento(&mut conn, id, TipoPago::Pago, solicitud.into_inner())?;
+    notificar_pago(broadcaster, &pago, &reserva);
+    Ok(Json(pago))
+}
+
+#[post("/reservas/<id>/reembolsos", format = "json", data = "<solicitud>")]
+fn registrar_reembolso(
+    pool: &State<DbPool>,
+    id: i32,
+    solicitud: Json<SolicitudPago>,
+    broadcaster: &State<Broadcaster>,
+) -> ApiResult<Json<Pago>> {
+    let mut conn = pool.get()?;
+    let (pago, reserva) =
+        pagos_service::registrar_movimiento(&mut conn, id, TipoPago::Reembolso, solicitud.into_inner())?;
+    notificar_pago(broadcaster, &pago, &reserva);
+    Ok(Json(pago))
+}
+
+/// 📡 Avisa a la pantalla de sala con el movimiento y el saldo resultante
+fn notificar_pago(broadcaster: &Broadcaster, pago: &Pago, reserva: &Reserva) {
+    broadcaster.send_evento(
+        pago.tipo.as_str(),
+        &serde_json::json!({
+            "pago": pago,
+            "reserva_id": reserva.id,
+            "cabana_id": reserva.cabana_id,
+            "monto_pagado": reserva.monto_pagado,
+            "saldo": reserva.saldo,
+        }),
+    );
+    broadcaster.send("actualizar");
+}
+
+// =========================
+// 🧾 COMPROBANTES
+// =========================
+/// HTML por defecto; `?formato=pdf` para descargar el PDF.
+/// Solo reimprime: 404 si la reserva todavía no tiene comprobante
+#[get("/reservas/<id>/comprobante?<formato>")]
+fn comprobante_reserva(
+    pool: &State<DbPool>,
+    id: i32,
+    formato: Option<&str>,
+) -> ApiResult<(ContentType, Vec<u8>)> {
+    let formato = formato_comprobante(formato)?;
+    let mut conn = pool.get()?;
+    let doc = comprobantes_service::obtener(&mut conn, id)?;
+    Ok(renderizar_comprobante(&doc, formato))
+}
+
+/// Emite (numera) el comprobante; si ya existe devuelve el mismo
+#[post("/reservas/<id>/comprobante?<formato>")]
+fn emitir_comprobante(
+    pool: &State<DbPool>,
+    id: i32,
+    formato: Option<&str>,
+) -> ApiResult<(ContentType, Vec<u8>)> {
+    let formato = formato_comprobante(formato)?;
+    let mut conn = pool.get()?;
+    let doc = comprobantes_service::emitir(&mut conn, id)?;
+    Ok(renderizar_comprobante(&doc, formato))
+}
+
+fn formato_comprobante(formato: Option<&str>) -> ApiResult<&str> {
+    let formato = formato.unwrap_or("html");
+    if !matches!(formato, "html" | "pdf") {
+        return Err(ApiError::validacion(format!("Formato '{}' no soportado (html, pdf)", formato))
+            .con_detalles(serde_json::json!({ "campo": "formato" })));
+    }
+    Ok(formato)
+}
+
+fn renderizar_comprobante(doc: &comprobantes_service::DocumentoComprobante, formato: &str) -> (ContentType, Vec<u8>) {
+    if formato == "pdf" {
+        (ContentType::PDF, comprobantes_service::renderizar_pdf(doc))
+    } else {
+        (ContentType::HTML, comprobantes_service::renderizar_html(doc).into_bytes())
+    }
 }
 
 // =========================
 // 🌍 CORS + ACTUALIZACIÓN AUTOMÁTICA
 // =========================
 use rocket_cors::{AllowedHeaders, AllowedOrigins, CorsOptions};
-use rocket::http::Method;
+use rocket::http::{ContentType, Method};
 use rocket::tokio;
 
 #[launch]
@@ -153,7 +883,7 @@ fn rocket() -> _ {
 
     let cors = CorsOptions {
         allowed_origins: AllowedOrigins::all(),
-        allowed_methods: vec![Method::Get, Method::Post, Method::Put, Method::Delete, Method::Options]
+        allowed_methods: vec![Method::Get, Method::Post, Method::Put, Method::Patch, Method::Delete, Method::Options]
             .into_iter()
             .map(From::from)
             .collect(),
@@ -174,16 +904,80 @@ fn rocket() -> _ {
                 index,
                 listar_clientes,
                 crear_cliente,
+                obtener_cliente,
+                reemplazar_cliente,
+                actualizar_cliente,
+                eliminar_cliente,
                 listar_cabanas,
+                obtener_cabana,
                 crear_cabana,
+                actualizar_cabana,
+                archivar_cabana,
                 actualizar_estado_cabana,
+                listar_mantenimientos,
+                obtener_mantenimiento,
+                crear_mantenimiento,
+                actualizar_mantenimiento,
+                eliminar_mantenimiento,
+                obtener_configuracion,
+                actualizar_configuracion,
+                listar_reglas_reserva,
+                limites_reserva,
+                crear_regla_reserva,
+                actualizar_regla_reserva,
+                eliminar_regla_reserva,
+                listar_horario,
+                actualizar_horario,
+                listar_excepciones_horario,
+                guardar_excepcion_horario,
+                eliminar_excepcion_horario,
+                disponibilidad_cabana,
+                buscar_disponibilidad,
+                listar_reglas_precio,
+                obtener_regla_precio,
+                crear_regla_precio,
+                actualizar_regla_precio,
+                eliminar_regla_precio,
+                listar_feriados,
+                crear_feriado,
+                eliminar_feriado,
+                tarifas_cabana,
+                listar_promociones,
+                obtener_promocion,
+                crear_promocion,
+                actualizar_promocion,
+                eliminar_promocion,
                 listar_reservas,
                 crear_reserva,
+                cotizar_reserva,
+                actualizar_reserva,
                 eliminar_reserva,
-                actualizar_estado_reserva
+                actualizar_estado_reserva,
+                check_in_reserva,
+                check_out_reserva,
+                listar_bloqueos,
+                crear_bloqueo,
+                confirmar_bloqueo,
+                liberar_bloqueo,
+                crear_serie,
+                obtener_serie,
+                actualizar_serie,
+                cancelar_serie,
+                listar_lista_espera,
+                crear_entrada_espera,
+                cancelar_entrada_espera,
+                listar_ofertas_espera,
+                aceptar_oferta_espera,
+                rechazar_oferta_espera,
+                listar_pagos,
+                registrar_pago,
+                registrar_reembolso,
+                comprobante_reserva,
+                emitir_comprobante
             ],
         )
         .mount("/ws", routes![ws::ws])
+        .register("/", catchers![errors::catcher_por_defecto])
         .attach(AdHoc::on_liftoff("Background Updater", move |_| {
             let pool_clone = pool.clone();
             let bc_clone = broadcaster.clone();
@@ -194,12 +988,33 @@ fn rocket() -> _ {
                         tokio::time::sleep(Duration::from_secs(5)).await;
                         if let Ok(mut conn) = pool_clone.get() {
                             match reservas_service::actualizar_estados_automaticos(&mut conn) {
-                                Ok(_) => {
+                                Ok((0, _)) => {}
+                                Ok((_, ofertas)) => {
+                                    notificar_ofertas(&bc_clone, &ofertas);
                                     bc_clone.send("actualizar");
                                     println!("⏱️ Estados actualizados automáticamente.");
                                 }
                                 Err(e) => eprintln!("⚠️ Error al actualizar estados automáticos: {:?}", e),
                             }
+                            match lista_espera_service::vencer_ofertas(&mut conn) {
+                                Ok((0, _)) => {}
+                                Ok((_, ofertas)) => {
+                                    notificar_ofertas(&bc_clone, &ofertas);
+                                    bc_clone.send("actualizar");
+                                }
+                                Err(e) => eprintln!("⚠️ Error al vencer ofertas de lista de espera: {:?}", e),
+                            }
+                            match bloqueos_service::vencer_bloqueos(&mut conn) {
+                                Ok((vencidos, _)) if vencidos.is_empty() => {}
+                                Ok((vencidos, ofertas)) => {
+                                    for bloqueo in &vencidos {
+                                        bc_clone.send_evento("bloqueo_vencido", bloqueo);
+                                    }
+                                    notificar_ofertas(&bc_clone, &ofertas);
+                                    bc_clone.send("actualizar");
+                                }
+                                Err(e) => eprintln!("⚠️ Error al limpiar bloqueos vencidos: {:?}", e),
+                            }
                         }
                     }
                 });
@@ -1,7 +1,175 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use std::io::Write;
+use crate::errors::{ApiError, ErrorBody};
 use crate::schema::*;
+use rocket::FromForm;
 use serde::{Serialize, Deserialize};
 
+// =============================
+// 🏷️ ESTADOS
+// =============================
+/// Estado de una reserva (tipo ENUM `estado_reserva` en PostgreSQL)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::EstadoReserva)]
+pub enum EstadoReserva {
+    #[default]
+    #[serde(rename = "pendiente")]
+    Pendiente,
+    #[serde(rename = "confirmada")]
+    Confirmada,
+    #[serde(rename = "en curso")]
+    EnCurso,
+    #[serde(rename = "completada")]
+    Completada,
+    #[serde(rename = "cancelada")]
+    Cancelada,
+    #[serde(rename = "no_show")]
+    NoShow,
+}
+
+impl EstadoReserva {
+    pub const TODOS: [EstadoReserva; 6] = [
+        EstadoReserva::Pendiente,
+        EstadoReserva::Confirmada,
+        EstadoReserva::EnCurso,
+        EstadoReserva::Completada,
+        EstadoReserva::Cancelada,
+        EstadoReserva::NoShow,
+    ];
+
+    /// Estados que ya no ocupan la cabaña: no cuentan para solapamientos
+    /// ni para la disponibilidad
+    pub const LIBERAN_CABANA: [EstadoReserva; 2] = [EstadoReserva::Cancelada, EstadoReserva::NoShow];
+
+    /// Estados con los que se puede crear una reserva; el resto solo se
+    /// alcanza por la tabla de transiciones. 'en curso' es el cliente que
+    /// llega sin reserva previa
+    pub const INICIALES: [EstadoReserva; 3] =
+        [EstadoReserva::Pendiente, EstadoReserva::Confirmada, EstadoReserva::EnCurso];
+
+    /// Estados sin transiciones de salida
+    pub const FINALES: [EstadoReserva; 3] =
+        [EstadoReserva::Completada, EstadoReserva::Cancelada, EstadoReserva::NoShow];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoReserva::Pendiente => "pendiente",
+            EstadoReserva::Confirmada => "confirmada",
+            EstadoReserva::EnCurso => "en curso",
+            EstadoReserva::Completada => "completada",
+            EstadoReserva::Cancelada => "cancelada",
+            EstadoReserva::NoShow => "no_show",
+        }
+    }
+
+    /// 🔀 Tabla de transiciones permitidas
+    ///
+    /// pendiente → confirmada → en curso (check-in) → completada (check-out)
+    /// cancelada y no_show solo antes del check-in
+    pub fn transiciones(&self) -> &'static [EstadoReserva] {
+        use EstadoReserva::*;
+        match self {
+            Pendiente => &[Confirmada, EnCurso, Cancelada, NoShow],
+            Confirmada => &[EnCurso, Cancelada, NoShow],
+            EnCurso => &[Completada],
+            Completada | Cancelada | NoShow => &[],
+        }
+    }
+
+    pub fn puede_pasar_a(&self, destino: EstadoReserva) -> bool {
+        self.transiciones().contains(&destino)
+    }
+
+    /// Un estado final ya no admite cambios
+    pub fn es_final(&self) -> bool {
+        Self::FINALES.contains(self)
+    }
+}
+
+/// Estado administrativo de una cabaña (tipo ENUM `estado_cabana` en PostgreSQL).
+/// Si está ocupada o en mantenimiento no se guarda: ver `OcupacionActual`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::EstadoCabana)]
+pub enum EstadoCabana {
+    #[default]
+    #[serde(rename = "activa")]
+    Activa,
+    #[serde(rename = "fuera de servicio")]
+    FueraDeServicio,
+}
+
+impl EstadoCabana {
+    pub const TODOS: [EstadoCabana; 2] = [EstadoCabana::Activa, EstadoCabana::FueraDeServicio];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoCabana::Activa => "activa",
+            EstadoCabana::FueraDeServicio => "fuera de servicio",
+        }
+    }
+}
+
+/// Implementa `Display`, `FromStr` (para rutas y filtros) y el mapeo
+/// Diesel `ToSql` / `FromSql` contra el tipo ENUM de PostgreSQL
+macro_rules! pg_enum {
+    ($tipo:ident, $sql:path, $campo:literal, $etiqueta:literal) => {
+        impl std::fmt::Display for $tipo {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $tipo {
+            type Err = ApiError;
+
+            /// Acepta también `_` en lugar de espacios (`en_curso`), útil en URLs
+            fn from_str(valor: &str) -> Result<Self, Self::Err> {
+                let valor_min = valor.trim().to_lowercase();
+                let normalizado = valor_min.replace('_', " ");
+                $tipo::TODOS
+                    .into_iter()
+                    .find(|e| e.as_str() == valor_min || e.as_str() == normalizado)
+                    .ok_or_else(|| {
+                        let validos: Vec<&str> = $tipo::TODOS.iter().map(|e| e.as_str()).collect();
+                        ApiError::validacion(format!("{} no válido: '{}'", $etiqueta, valor))
+                            .con_detalles(serde_json::json!({ "campo": $campo, "validos": validos }))
+                    })
+            }
+        }
+
+        impl ToSql<$sql, Pg> for $tipo {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+                out.write_all(self.as_str().as_bytes())?;
+                Ok(IsNull::No)
+            }
+        }
+
+        impl FromSql<$sql, Pg> for $tipo {
+            fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+                let texto = std::str::from_utf8(bytes.as_bytes())?;
+                $tipo::TODOS
+                    .into_iter()
+                    .find(|e| e.as_str() == texto)
+                    .ok_or_else(|| format!("{} desconocido: {}", $etiqueta, texto).into())
+            }
+        }
+    };
+}
+
+pg_enum!(EstadoReserva, crate::schema::sql_types::EstadoReserva, "estado", "Estado de reserva");
+pg_enum!(EstadoCabana, crate::schema::sql_types::EstadoCabana, "estado", "Estado de cabaña");
+pg_enum!(ModoReglaPrecio, crate::schema::sql_types::ModoReglaPrecio, "modo", "Modo de regla de precio");
+pg_enum!(MetodoPago, crate::schema::sql_types::MetodoPago, "metodo", "Método de pago");
+pg_enum!(TipoDescuento, crate::schema::sql_types::TipoDescuento, "tipo", "Tipo de descuento");
+pg_enum!(TipoPago, crate::schema::sql_types::TipoPago, "tipo", "Tipo de movimiento");
+pg_enum!(EstadoEspera, crate::schema::sql_types::EstadoEspera, "estado", "Estado de lista de espera");
+pg_enum!(EstadoOferta, crate::schema::sql_types::EstadoOferta, "estado", "Estado de oferta");
+pg_enum!(FrecuenciaSerie, crate::schema::sql_types::FrecuenciaSerie, "frecuencia", "Frecuencia de serie");
+
 // =============================
 // 🧍 CLIENTES
 // =============================
@@ -14,15 +182,47 @@ pub struct Cliente {
     pub email: Option<String>,
     pub dni: Option<String>,
     pub fecha_registro: Option<chrono::NaiveDateTime>,
+    /// Datos fiscales para el comprobante
+    pub nit: Option<String>,
+    pub razon_social: Option<String>,
+    /// Reservas a las que no se presentó (ver `actualizar_estados_automaticos`)
+    pub no_shows: i32,
 }
 
-#[derive(Debug, Insertable, Serialize, Deserialize)]
-#[diesel(table_name = clientes)]
+/// Alta de un cliente; también es el cuerpo de `PUT /clientes/<id>`, que
+/// reemplaza todas las columnas (un campo ausente o `null` queda vacío)
+#[derive(Debug, Insertable, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = clientes, treat_none_as_null = true)]
 pub struct NewCliente<'a> {
     pub nombre: &'a str,
     pub telefono: Option<&'a str>,
     pub email: Option<&'a str>,
     pub dni: Option<&'a str>,
+    pub nit: Option<&'a str>,
+    pub razon_social: Option<&'a str>,
+}
+
+/// ✏️ Cambios parciales de un cliente (los campos ausentes no se tocan)
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = clientes)]
+pub struct UpdateCliente {
+    pub nombre: Option<String>,
+    pub telefono: Option<String>,
+    pub email: Option<String>,
+    pub dni: Option<String>,
+    pub nit: Option<String>,
+    pub razon_social: Option<String>,
+}
+
+impl UpdateCliente {
+    pub fn esta_vacio(&self) -> bool {
+        self.nombre.is_none()
+            && self.telefono.is_none()
+            && self.email.is_none()
+            && self.dni.is_none()
+            && self.nit.is_none()
+            && self.razon_social.is_none()
+    }
 }
 
 // =============================
@@ -35,9 +235,16 @@ pub struct Cabana {
     pub nombre: String,
     pub capacidad: i32,
     pub ubicacion: Option<String>,
-    pub estado: String,
+    pub estado: EstadoCabana,
     pub descripcion: Option<String>,
     pub precio_hora: Option<bigdecimal::BigDecimal>,
+    pub archivada: bool,
+    /// Mínimo de personas para reservarla (las VIP grandes no se dan a parejas)
+    pub ocupacion_minima: i32,
+    /// Margen de limpieza propio; `None` = el de `Configuracion`
+    pub minutos_limpieza: Option<i32>,
+    /// Zona a la que apuntan las reglas de precio por zona (coincidencia exacta)
+    pub zona: Option<String>,
 }
 
 #[derive(Debug, Insertable, Serialize, Deserialize)]
@@ -46,9 +253,80 @@ pub struct NewCabana<'a> {
     pub nombre: &'a str,
     pub capacidad: i32,
     pub ubicacion: Option<&'a str>,
-    pub estado: &'a str,
+    #[serde(default)]
+    pub estado: EstadoCabana,
     pub descripcion: Option<&'a str>,
     pub precio_hora: Option<bigdecimal::BigDecimal>,
+    pub ocupacion_minima: Option<i32>,
+    pub minutos_limpieza: Option<i32>,
+    pub zona: Option<&'a str>,
+}
+
+/// ✏️ Cambios parciales de una cabaña (el estado tiene su propia ruta)
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = cabanas)]
+pub struct UpdateCabana {
+    pub nombre: Option<String>,
+    pub capacidad: Option<i32>,
+    pub ubicacion: Option<String>,
+    pub descripcion: Option<String>,
+    pub precio_hora: Option<bigdecimal::BigDecimal>,
+    pub ocupacion_minima: Option<i32>,
+    /// Ausente = no se toca; `null` = vuelve al margen de `Configuracion`
+    #[serde(default, deserialize_with = "campo_anulable")]
+    pub minutos_limpieza: Option<Option<i32>>,
+    pub zona: Option<String>,
+}
+
+impl UpdateCabana {
+    pub fn esta_vacio(&self) -> bool {
+        self.nombre.is_none()
+            && self.capacidad.is_none()
+            && self.ubicacion.is_none()
+            && self.descripcion.is_none()
+            && self.precio_hora.is_none()
+            && self.ocupacion_minima.is_none()
+            && self.minutos_limpieza.is_none()
+            && self.zona.is_none()
+    }
+}
+
+/// Para cambios parciales de columnas anulables: distingue el campo ausente
+/// (`None`, no se toca) del enviado como `null` (`Some(None)`, se borra)
+fn campo_anulable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Qué pasa con la cabaña en este momento (calculado, no se guarda)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EstadoOcupacion {
+    #[serde(rename = "libre")]
+    Libre,
+    #[serde(rename = "ocupada")]
+    Ocupada,
+    #[serde(rename = "mantenimiento")]
+    Mantenimiento,
+}
+
+/// Ocupación en vivo de una cabaña a partir de sus reservas y mantenimientos
+#[derive(Debug, Serialize)]
+pub struct OcupacionActual {
+    pub estado: EstadoOcupacion,
+    pub reserva_actual: Option<Reserva>,
+    pub proxima_reserva: Option<Reserva>,
+    pub mantenimiento_actual: Option<Mantenimiento>,
+}
+
+/// Cabaña tal como la devuelven `GET /cabanas` y `GET /cabanas/<id>`
+#[derive(Debug, Serialize)]
+pub struct CabanaConOcupacion {
+    #[serde(flatten)]
+    pub cabana: Cabana,
+    pub ocupacion_actual: OcupacionActual,
 }
 
 // =============================
@@ -65,17 +343,29 @@ pub struct Reserva {
     pub fecha_reserva: chrono::NaiveDate,
     pub hora_inicio: chrono::NaiveTime,
     pub hora_fin: chrono::NaiveTime,
-    pub estado: String,
+    pub estado: EstadoReserva,
     pub observaciones: Option<String>,
     pub fecha_creacion: Option<chrono::NaiveDateTime>,
+    /// Instantes reales (calculados en la BD); `fin` cae al día siguiente
+    /// cuando la reserva cruza la medianoche
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub num_personas: i32,
+    /// Precio congelado al reservar (ver `precios_service`)
+    pub monto_total: Option<bigdecimal::BigDecimal>,
+    /// Pagos menos reembolsos registrados (ver `pagos_service`)
+    pub monto_pagado: bigdecimal::BigDecimal,
+    /// monto_total - monto_pagado (calculado en la BD); negativo = saldo a favor
+    pub saldo: Option<bigdecimal::BigDecimal>,
+    /// Descuento de la promoción canjeada (ya restado de monto_total)
+    pub descuento: bigdecimal::BigDecimal,
+    /// Serie recurrente que la generó, si la hay
+    pub serie_id: Option<i32>,
+    /// Check-in y check-out reales
+    pub llegada: Option<chrono::NaiveDateTime>,
+    pub salida: Option<chrono::NaiveDateTime>,
 }
 
-// ⚙️ Valor por defecto para el campo "estado"
-fn estado_por_defecto() -> String {
-    "pendiente".to_string()
-}
-
-
 #[derive(Debug, Insertable, Serialize, Deserialize)]
 #[diesel(table_name = reservas)]
 pub struct NewReserva {
@@ -84,8 +374,1103 @@ pub struct NewReserva {
     pub fecha_reserva: chrono::NaiveDate,
     pub hora_inicio: chrono::NaiveTime,
     pub hora_fin: chrono::NaiveTime,
-    #[serde(default = "estado_por_defecto")] // 👈 Valor por defecto si no se envía
-    pub estado: String,
+    #[serde(default)] // 👈 "pendiente" si no se envía
+    pub estado: EstadoReserva,
+    pub observaciones: Option<String>,
+    pub num_personas: i32,
+    /// Código de promoción a canjear (ver `promociones_service`)
+    #[diesel(skip_insertion)]
+    pub codigo_promocion: Option<String>,
+    /// Reservar aunque no se respete el margen de limpieza con la reserva vecina
+    #[serde(default)]
+    #[diesel(skip_insertion)]
+    pub ignorar_limpieza: bool,
+    /// Solo lo fija `series_service` al generar las ocurrencias
+    #[serde(skip_deserializing)]
+    pub serie_id: Option<i32>,
+}
+
+/// ✏️ Cambios para reprogramar o editar una reserva existente
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = reservas)]
+pub struct UpdateReserva {
+    pub cabana_id: Option<i32>,
+    pub fecha_reserva: Option<chrono::NaiveDate>,
+    pub hora_inicio: Option<chrono::NaiveTime>,
+    pub hora_fin: Option<chrono::NaiveTime>,
+    /// Ausente = no se toca; `null` = se borran
+    #[serde(default, deserialize_with = "campo_anulable")]
+    pub observaciones: Option<Option<String>>,
+    pub num_personas: Option<i32>,
+    /// Reprogramar aunque no se respete el margen de limpieza
+    #[serde(default)]
+    #[diesel(skip_update)]
+    pub ignorar_limpieza: bool,
+}
+
+impl UpdateReserva {
+    pub fn esta_vacio(&self) -> bool {
+        self.cabana_id.is_none()
+            && self.fecha_reserva.is_none()
+            && self.hora_inicio.is_none()
+            && self.hora_fin.is_none()
+            && self.observaciones.is_none()
+            && self.num_personas.is_none()
+    }
+}
+
+/// 🔎 Parámetros de consulta de `GET /reservas`
+///  - `desde` / `hasta`: rango de `fecha_reserva` (YYYY-MM-DD, inclusivo)
+///  - `estado`: se puede repetir (`?estado=pendiente&estado=en curso`)
+///  - `q`: texto libre sobre `observaciones`
+///  - `orden`: `asc` (por defecto) o `desc`
+#[derive(Debug, FromForm)]
+pub struct FiltroReservas {
+    pub desde: Option<String>,
+    pub hasta: Option<String>,
+    pub cabana_id: Option<i32>,
+    pub cliente_id: Option<i32>,
+    pub serie_id: Option<i32>,
+    pub estado: Vec<String>,
+    pub q: Option<String>,
+    pub orden: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// 📄 Página de resultados con metadatos de paginación
+#[derive(Debug, Serialize)]
+pub struct Pagina<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+// =============================
+// 🟢 DISPONIBILIDAD
+// =============================
+/// Intervalo semiabierto `[inicio, fin)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Intervalo {
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+}
+
+/// Huecos libres de una cabaña dentro de la ventana consultada
+#[derive(Debug, Serialize)]
+pub struct DisponibilidadCabana {
+    pub cabana_id: i32,
+    pub nombre: String,
+    pub capacidad: i32,
+    pub ocupacion_minima: i32,
+    pub estado: EstadoCabana,
+    pub libres: Vec<Intervalo>,
+}
+
+/// 🔎 Parámetros de consulta de `GET /disponibilidad`
+///  - `fecha`: día consultado (hoy si se omite)
+///  - `desde` / `hasta`: ventana horaria (HH:MM); si `hasta <= desde` termina al día siguiente
+///  - `capacidad`: tamaño del grupo; la cabaña debe admitirlo y no exigir más ocupación mínima
+#[derive(Debug, FromForm)]
+pub struct FiltroDisponibilidad {
+    pub fecha: Option<String>,
+    pub desde: Option<String>,
+    pub hasta: Option<String>,
+    pub capacidad: Option<i32>,
+}
+
+// =============================
+// 💰 PRECIOS
+// =============================
+/// Datos mínimos para cotizar una reserva sin crearla
+#[derive(Debug, Deserialize)]
+pub struct SolicitudCotizacion {
+    pub cabana_id: i32,
+    pub fecha_reserva: chrono::NaiveDate,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub codigo_promocion: Option<String>,
+    /// Para controlar el límite de usos por cliente del código
+    pub cliente_id: Option<i32>,
+}
+
+/// Parte de la reserva cobrada a una misma tarifa
+#[derive(Debug, Clone, Serialize)]
+pub struct TramoPrecio {
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub horas: bigdecimal::BigDecimal,
+    pub precio_hora: bigdecimal::BigDecimal,
+    pub subtotal: bigdecimal::BigDecimal,
+    /// Regla de precio aplicada (`None` = precio_hora base de la cabaña)
+    pub regla_id: Option<i32>,
+    pub regla: Option<String>,
+}
+
+/// Desglose del precio de una reserva
+#[derive(Debug, Serialize)]
+pub struct Cotizacion {
+    pub cabana_id: i32,
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    /// Duración real de la reserva
+    pub minutos: i64,
+    /// Duración cobrada tras redondear al bloque de facturación
+    pub minutos_facturados: i64,
+    pub tramos: Vec<TramoPrecio>,
+    /// Descuento por código de promoción (0 si no se envió)
+    pub descuento: bigdecimal::BigDecimal,
+    pub codigo_promocion: Option<String>,
+    /// Suma de los tramos menos el descuento
+    pub monto_total: bigdecimal::BigDecimal,
+}
+
+/// Cómo modifica una regla el precio por hora (tipo ENUM `modo_regla_precio`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::ModoReglaPrecio)]
+pub enum ModoReglaPrecio {
+    /// precio_hora de la cabaña × valor
+    #[serde(rename = "multiplicador")]
+    Multiplicador,
+    /// valor es el precio por hora absoluto
+    #[serde(rename = "tarifa")]
+    Tarifa,
+}
+
+impl ModoReglaPrecio {
+    pub const TODOS: [ModoReglaPrecio; 2] = [ModoReglaPrecio::Multiplicador, ModoReglaPrecio::Tarifa];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModoReglaPrecio::Multiplicador => "multiplicador",
+            ModoReglaPrecio::Tarifa => "tarifa",
+        }
+    }
+}
+
+/// 📈 Regla de precio dinámico (ver migración `reglas_precio` para la semántica)
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = reglas_precio)]
+pub struct ReglaPrecio {
+    pub id: i32,
+    pub nombre: String,
+    pub cabana_id: Option<i32>,
+    pub zona: Option<String>,
+    /// 1 = lunes ... 7 = domingo
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    pub fecha_desde: Option<chrono::NaiveDate>,
+    pub fecha_hasta: Option<chrono::NaiveDate>,
+    pub solo_feriados: bool,
+    pub modo: ModoReglaPrecio,
+    pub valor: bigdecimal::BigDecimal,
+    pub prioridad: i32,
+    pub activa: bool,
+}
+
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = reglas_precio)]
+pub struct NewReglaPrecio {
+    pub nombre: String,
+    pub cabana_id: Option<i32>,
+    pub zona: Option<String>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    pub fecha_desde: Option<chrono::NaiveDate>,
+    pub fecha_hasta: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub solo_feriados: bool,
+    pub modo: ModoReglaPrecio,
+    pub valor: bigdecimal::BigDecimal,
+    #[serde(default)]
+    pub prioridad: i32,
+    pub activa: Option<bool>,
+}
+
+/// ✏️ Cambios parciales de una regla (los campos ausentes no se tocan)
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = reglas_precio)]
+pub struct UpdateReglaPrecio {
+    pub nombre: Option<String>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    pub fecha_desde: Option<chrono::NaiveDate>,
+    pub fecha_hasta: Option<chrono::NaiveDate>,
+    pub solo_feriados: Option<bool>,
+    pub modo: Option<ModoReglaPrecio>,
+    pub valor: Option<bigdecimal::BigDecimal>,
+    pub prioridad: Option<i32>,
+    pub activa: Option<bool>,
+}
+
+impl UpdateReglaPrecio {
+    pub fn esta_vacio(&self) -> bool {
+        self.nombre.is_none()
+            && self.dias_semana.is_none()
+            && self.hora_desde.is_none()
+            && self.hora_hasta.is_none()
+            && self.fecha_desde.is_none()
+            && self.fecha_hasta.is_none()
+            && self.solo_feriados.is_none()
+            && self.modo.is_none()
+            && self.valor.is_none()
+            && self.prioridad.is_none()
+            && self.activa.is_none()
+    }
+}
+
+/// 🎉 Día feriado (las reglas con `solo_feriados` aplican solo estos días)
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = feriados)]
+pub struct Feriado {
+    pub fecha: chrono::NaiveDate,
+    pub nombre: String,
+}
+
+/// Tarifa efectiva de una cabaña en un día, partida por reglas
+#[derive(Debug, Serialize)]
+pub struct DiaTarifas {
+    pub fecha: chrono::NaiveDate,
+    pub tramos: Vec<TramoTarifa>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TramoTarifa {
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    /// `None` si la cabaña no tiene precio base y ninguna regla fija tarifa
+    pub precio_hora: Option<bigdecimal::BigDecimal>,
+    pub regla_id: Option<i32>,
+    pub regla: Option<String>,
+}
+
+// =============================
+// 💵 PAGOS
+// =============================
+/// Medio de cobro (tipo ENUM `metodo_pago`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::MetodoPago)]
+pub enum MetodoPago {
+    #[serde(rename = "efectivo")]
+    Efectivo,
+    #[serde(rename = "tarjeta")]
+    Tarjeta,
+    /// Transferencia bancaria por QR
+    #[serde(rename = "qr")]
+    Qr,
+}
+
+impl MetodoPago {
+    pub const TODOS: [MetodoPago; 3] = [MetodoPago::Efectivo, MetodoPago::Tarjeta, MetodoPago::Qr];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetodoPago::Efectivo => "efectivo",
+            MetodoPago::Tarjeta => "tarjeta",
+            MetodoPago::Qr => "qr",
+        }
+    }
+}
+
+/// Sentido del movimiento (tipo ENUM `tipo_pago`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::TipoPago)]
+pub enum TipoPago {
+    #[serde(rename = "pago")]
+    Pago,
+    #[serde(rename = "reembolso")]
+    Reembolso,
+}
+
+impl TipoPago {
+    pub const TODOS: [TipoPago; 2] = [TipoPago::Pago, TipoPago::Reembolso];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoPago::Pago => "pago",
+            TipoPago::Reembolso => "reembolso",
+        }
+    }
+}
+
+/// Movimiento de dinero de una reserva (pago o reembolso, monto siempre positivo)
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Serialize, Deserialize)]
+#[diesel(belongs_to(Reserva))]
+#[diesel(table_name = pagos)]
+pub struct Pago {
+    pub id: i32,
+    pub reserva_id: i32,
+    pub tipo: TipoPago,
+    pub monto: bigdecimal::BigDecimal,
+    pub metodo: MetodoPago,
+    pub referencia: Option<String>,
+    /// Usuario del staff que registró el movimiento
+    pub registrado_por: String,
+    pub fecha_pago: chrono::NaiveDateTime,
+}
+
+/// Cuerpo de POST /reservas/<id>/pagos y /reservas/<id>/reembolsos
+#[derive(Debug, Deserialize)]
+pub struct SolicitudPago {
+    pub monto: bigdecimal::BigDecimal,
+    pub metodo: MetodoPago,
+    pub referencia: Option<String>,
+    pub registrado_por: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = pagos)]
+pub struct NewPago {
+    pub reserva_id: i32,
+    pub tipo: TipoPago,
+    pub monto: bigdecimal::BigDecimal,
+    pub metodo: MetodoPago,
+    pub referencia: Option<String>,
+    pub registrado_por: String,
+}
+
+/// Estado de cuenta de una reserva
+#[derive(Debug, Serialize)]
+pub struct EstadoCuenta {
+    pub reserva_id: i32,
+    pub monto_total: Option<bigdecimal::BigDecimal>,
+    pub monto_pagado: bigdecimal::BigDecimal,
+    pub saldo: Option<bigdecimal::BigDecimal>,
+    pub pagos: Vec<Pago>,
+}
+
+// =============================
+// 🧾 COMPROBANTES
+// =============================
+/// Comprobante emitido para una reserva completada (datos congelados al emitir)
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Serialize, Deserialize)]
+#[diesel(belongs_to(Reserva))]
+#[diesel(table_name = comprobantes)]
+pub struct Comprobante {
+    pub id: i32,
+    /// Correlativo sin huecos
+    pub numero: i32,
+    pub reserva_id: i32,
+    pub fecha_emision: chrono::NaiveDateTime,
+    pub nombre_cliente: String,
+    pub nit: Option<String>,
+    pub razon_social: Option<String>,
+    pub descripcion: String,
+    pub base_imponible: bigdecimal::BigDecimal,
+    /// Porcentaje, p. ej. 13.00
+    pub tasa_impuesto: bigdecimal::BigDecimal,
+    pub impuesto: bigdecimal::BigDecimal,
+    pub total: bigdecimal::BigDecimal,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = comprobantes)]
+pub struct NewComprobante {
+    pub numero: i32,
+    pub reserva_id: i32,
+    pub nombre_cliente: String,
+    pub nit: Option<String>,
+    pub razon_social: Option<String>,
+    pub descripcion: String,
+    pub base_imponible: bigdecimal::BigDecimal,
+    pub tasa_impuesto: bigdecimal::BigDecimal,
+    pub impuesto: bigdecimal::BigDecimal,
+    pub total: bigdecimal::BigDecimal,
+}
+
+// =============================
+// 🏷️ PROMOCIONES
+// =============================
+/// Tipo de descuento (tipo ENUM `tipo_descuento`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::TipoDescuento)]
+pub enum TipoDescuento {
+    /// valor = % sobre el monto de la reserva
+    #[serde(rename = "porcentaje")]
+    Porcentaje,
+    /// valor = importe a descontar (nunca deja el total por debajo de 0)
+    #[serde(rename = "monto_fijo")]
+    MontoFijo,
+}
+
+impl TipoDescuento {
+    pub const TODOS: [TipoDescuento; 2] = [TipoDescuento::Porcentaje, TipoDescuento::MontoFijo];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TipoDescuento::Porcentaje => "porcentaje",
+            TipoDescuento::MontoFijo => "monto_fijo",
+        }
+    }
+}
+
+/// Promoción canjeable con un código (ver migración `promociones` para las restricciones)
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = promociones)]
+pub struct Promocion {
+    pub id: i32,
+    pub codigo: String,
+    pub descripcion: Option<String>,
+    pub tipo: TipoDescuento,
+    pub valor: bigdecimal::BigDecimal,
+    pub vigente_desde: Option<chrono::NaiveDate>,
+    pub vigente_hasta: Option<chrono::NaiveDate>,
+    /// 1 = lunes ... 7 = domingo
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    /// `None` = todas las cabañas
+    pub cabana_ids: Option<Vec<i32>>,
+    pub usos_maximos: Option<i32>,
+    pub usos_por_cliente: Option<i32>,
+    pub horas_minimas: Option<bigdecimal::BigDecimal>,
+    pub activa: bool,
+    pub fecha_creacion: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = promociones)]
+pub struct NewPromocion {
+    pub codigo: String,
+    pub descripcion: Option<String>,
+    pub tipo: TipoDescuento,
+    pub valor: bigdecimal::BigDecimal,
+    pub vigente_desde: Option<chrono::NaiveDate>,
+    pub vigente_hasta: Option<chrono::NaiveDate>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    pub cabana_ids: Option<Vec<i32>>,
+    pub usos_maximos: Option<i32>,
+    pub usos_por_cliente: Option<i32>,
+    pub horas_minimas: Option<bigdecimal::BigDecimal>,
+    pub activa: Option<bool>,
+}
+
+/// ✏️ Cambios parciales de una promoción (el código no se cambia)
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = promociones)]
+pub struct UpdatePromocion {
+    pub descripcion: Option<String>,
+    pub tipo: Option<TipoDescuento>,
+    pub valor: Option<bigdecimal::BigDecimal>,
+    pub vigente_desde: Option<chrono::NaiveDate>,
+    pub vigente_hasta: Option<chrono::NaiveDate>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub hora_desde: Option<chrono::NaiveTime>,
+    pub hora_hasta: Option<chrono::NaiveTime>,
+    pub cabana_ids: Option<Vec<i32>>,
+    pub usos_maximos: Option<i32>,
+    pub usos_por_cliente: Option<i32>,
+    pub horas_minimas: Option<bigdecimal::BigDecimal>,
+    pub activa: Option<bool>,
+}
+
+impl UpdatePromocion {
+    pub fn esta_vacio(&self) -> bool {
+        self.descripcion.is_none()
+            && self.tipo.is_none()
+            && self.valor.is_none()
+            && self.vigente_desde.is_none()
+            && self.vigente_hasta.is_none()
+            && self.dias_semana.is_none()
+            && self.hora_desde.is_none()
+            && self.hora_hasta.is_none()
+            && self.cabana_ids.is_none()
+            && self.usos_maximos.is_none()
+            && self.usos_por_cliente.is_none()
+            && self.horas_minimas.is_none()
+            && self.activa.is_none()
+    }
+}
+
+/// Uso de una promoción en una reserva
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Serialize, Deserialize)]
+#[diesel(belongs_to(Promocion))]
+#[diesel(belongs_to(Reserva))]
+#[diesel(table_name = canjes_promocion)]
+pub struct CanjePromocion {
+    pub id: i32,
+    pub promocion_id: i32,
+    pub reserva_id: i32,
+    pub cliente_id: i32,
+    pub descuento: bigdecimal::BigDecimal,
+    pub fecha_canje: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = canjes_promocion)]
+pub struct NewCanjePromocion {
+    pub promocion_id: i32,
+    pub reserva_id: i32,
+    pub cliente_id: i32,
+    pub descuento: bigdecimal::BigDecimal,
+}
+
+// =============================
+// 🕘 HORARIO DE ATENCIÓN
+// =============================
+/// Horario de un día de la semana (1 = lunes ... 7 = domingo).
+/// `cierra <= abre` termina al día siguiente; `cierra == abre` es 24 h.
+#[derive(Debug, Clone, Queryable, Serialize, Deserialize)]
+#[diesel(table_name = horarios_atencion)]
+pub struct HorarioAtencion {
+    pub dia_semana: i32,
+    pub abre: Option<chrono::NaiveTime>,
+    pub cierra: Option<chrono::NaiveTime>,
+    pub cerrado: bool,
+}
+
+/// Cuerpo de PUT /horarios/<dia_semana>
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = horarios_atencion, treat_none_as_null = true)]
+pub struct HorarioDia {
+    pub abre: Option<chrono::NaiveTime>,
+    pub cierra: Option<chrono::NaiveTime>,
+    #[serde(default)]
+    pub cerrado: bool,
+}
+
+/// Día con horario distinto al semanal (cierre por feriado, evento privado...)
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = excepciones_horario)]
+pub struct ExcepcionHorario {
+    pub fecha: chrono::NaiveDate,
+    #[serde(default = "cerrado_por_defecto")]
+    pub cerrado: bool,
+    pub abre: Option<chrono::NaiveTime>,
+    pub cierra: Option<chrono::NaiveTime>,
+    pub motivo: Option<String>,
+}
+
+fn cerrado_por_defecto() -> bool {
+    true
+}
+
+// =============================
+// ⚙️ CONFIGURACIÓN
+// =============================
+/// Parámetros globales del negocio (una sola fila)
+#[derive(Debug, Clone, Queryable, Selectable, Serialize, Deserialize)]
+#[diesel(table_name = configuracion)]
+pub struct Configuracion {
+    /// Margen por defecto entre reservas de una misma cabaña
+    pub minutos_limpieza: i32,
+    /// Plazo para aceptar una oferta de la lista de espera
+    pub minutos_oferta_espera: i32,
+    /// Cuánto retiene un bloqueo temporal el horario
+    pub minutos_bloqueo: i32,
+    /// Minutos tras el inicio en que una reserva sin check-in pasa a no_show
+    pub minutos_tolerancia_no_show: i32,
+    /// Porcentaje de IVA incluido en los precios (se copia a cada comprobante)
+    pub tasa_iva: bigdecimal::BigDecimal,
+}
+
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = configuracion)]
+pub struct UpdateConfiguracion {
+    pub minutos_limpieza: Option<i32>,
+    pub minutos_oferta_espera: Option<i32>,
+    pub minutos_bloqueo: Option<i32>,
+    pub minutos_tolerancia_no_show: Option<i32>,
+    pub tasa_iva: Option<bigdecimal::BigDecimal>,
+}
+
+impl UpdateConfiguracion {
+    pub fn esta_vacio(&self) -> bool {
+        self.minutos_limpieza.is_none()
+            && self.minutos_oferta_espera.is_none()
+            && self.minutos_bloqueo.is_none()
+            && self.minutos_tolerancia_no_show.is_none()
+            && self.tasa_iva.is_none()
+    }
+}
+
+// =============================
+// 📏 REGLAS DE RESERVA
+// =============================
+/// Límites de duración, granularidad y anticipación (ver migración `reglas_reserva`)
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = reglas_reserva)]
+pub struct ReglaReserva {
+    pub id: i32,
+    pub cabana_id: Option<i32>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub duracion_minima: Option<i32>,
+    pub duracion_maxima: Option<i32>,
+    pub granularidad: Option<i32>,
+    pub anticipacion_minima: Option<i32>,
+    pub anticipacion_maxima_dias: Option<i32>,
+}
+
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = reglas_reserva)]
+pub struct NewReglaReserva {
+    pub cabana_id: Option<i32>,
+    pub dias_semana: Option<Vec<i32>>,
+    pub duracion_minima: Option<i32>,
+    pub duracion_maxima: Option<i32>,
+    pub granularidad: Option<i32>,
+    pub anticipacion_minima: Option<i32>,
+    pub anticipacion_maxima_dias: Option<i32>,
+}
+
+/// ✏️ Reemplaza los límites de una regla (la cabaña no se cambia);
+/// un límite ausente o `null` deja de opinar
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = reglas_reserva, treat_none_as_null = true)]
+pub struct UpdateReglaReserva {
+    /// Ausente = no se toca; `null` = pasa a aplicar todos los días
+    #[serde(default, deserialize_with = "campo_anulable")]
+    #[diesel(treat_none_as_null = false)]
+    pub dias_semana: Option<Option<Vec<i32>>>,
+    pub duracion_minima: Option<i32>,
+    pub duracion_maxima: Option<i32>,
+    pub granularidad: Option<i32>,
+    pub anticipacion_minima: Option<i32>,
+    pub anticipacion_maxima_dias: Option<i32>,
+}
+
+impl UpdateReglaReserva {
+    pub fn esta_vacio(&self) -> bool {
+        self.dias_semana.is_none()
+            && self.duracion_minima.is_none()
+            && self.duracion_maxima.is_none()
+            && self.granularidad.is_none()
+            && self.anticipacion_minima.is_none()
+            && self.anticipacion_maxima_dias.is_none()
+    }
+}
+
+/// Límites que aplican a una cabaña en una fecha, ya combinados
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LimitesReserva {
+    pub duracion_minima: Option<i32>,
+    pub duracion_maxima: Option<i32>,
+    pub granularidad: Option<i32>,
+    pub anticipacion_minima: Option<i32>,
+    pub anticipacion_maxima_dias: Option<i32>,
+}
+
+/// Una regla incumplida, para mostrar junto al campo del formulario
+#[derive(Debug, Serialize)]
+pub struct Violacion {
+    pub campo: &'static str,
+    pub regla: &'static str,
+    pub mensaje: String,
+    /// Límite configurado (minutos o días, según la regla)
+    pub limite: i32,
+}
+
+/// 🔎 Parámetros de `GET /reglas-reserva/efectivas`
+#[derive(Debug, FromForm)]
+pub struct FiltroLimites {
+    pub cabana_id: i32,
+    pub fecha: Option<String>,
+}
+
+// =============================
+// 🛠️ MANTENIMIENTOS
+// =============================
+/// Ventana `[inicio, fin)` en la que la cabaña no se puede reservar
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = mantenimientos)]
+pub struct Mantenimiento {
+    pub id: i32,
+    pub cabana_id: i32,
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub motivo: String,
+    pub fecha_creacion: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = mantenimientos)]
+pub struct NewMantenimiento {
+    pub cabana_id: i32,
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub motivo: String,
+}
+
+/// ✏️ Mover o acortar una ventana (la cabaña no se cambia)
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = mantenimientos)]
+pub struct UpdateMantenimiento {
+    pub inicio: Option<chrono::NaiveDateTime>,
+    pub fin: Option<chrono::NaiveDateTime>,
+    pub motivo: Option<String>,
+}
+
+impl UpdateMantenimiento {
+    pub fn esta_vacio(&self) -> bool {
+        self.inicio.is_none() && self.fin.is_none() && self.motivo.is_none()
+    }
+}
+
+/// Ventana junto con las reservas activas que quedaron dentro y hay que mover
+#[derive(Debug, Serialize)]
+pub struct DetalleMantenimiento {
+    pub mantenimiento: Mantenimiento,
+    pub reservas_afectadas: Vec<Reserva>,
+}
+
+/// 🔎 Parámetros de `GET /mantenimientos`
+#[derive(Debug, FromForm)]
+pub struct FiltroMantenimientos {
+    pub cabana_id: Option<i32>,
+    /// Solo ventanas que terminan después de esta fecha (por defecto, hoy)
+    pub desde: Option<String>,
+}
+
+// =============================
+// 🔁 SERIES DE RESERVAS
+// =============================
+/// Cada cuánto se repite una serie (tipo ENUM `frecuencia_serie`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::FrecuenciaSerie)]
+pub enum FrecuenciaSerie {
+    /// Mismo día de la semana que `fecha_inicio`
+    #[serde(rename = "semanal")]
+    Semanal,
+    /// Mismo día del mes; los meses que no lo tienen se saltean
+    #[serde(rename = "mensual")]
+    Mensual,
+}
+
+impl FrecuenciaSerie {
+    pub const TODOS: [FrecuenciaSerie; 2] = [FrecuenciaSerie::Semanal, FrecuenciaSerie::Mensual];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrecuenciaSerie::Semanal => "semanal",
+            FrecuenciaSerie::Mensual => "mensual",
+        }
+    }
+}
+
+/// Patrón con el que se generaron las reservas de una serie
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = series_reserva)]
+pub struct SerieReserva {
+    pub id: i32,
+    pub cliente_id: i32,
+    pub cabana_id: i32,
+    pub frecuencia: FrecuenciaSerie,
+    pub intervalo: i32,
+    pub fecha_inicio: chrono::NaiveDate,
+    pub hasta: Option<chrono::NaiveDate>,
+    pub repeticiones: Option<i32>,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub num_personas: i32,
+    pub observaciones: Option<String>,
+    pub fecha_creacion: chrono::NaiveDateTime,
+}
+
+/// ➕ Pedido de una serie: el patrón más las opciones de creación
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = series_reserva)]
+pub struct NewSerieReserva {
+    pub cliente_id: i32,
+    pub cabana_id: i32,
+    pub frecuencia: FrecuenciaSerie,
+    #[serde(default = "intervalo_por_defecto")]
+    pub intervalo: i32,
+    pub fecha_inicio: chrono::NaiveDate,
+    /// Última fecha posible (inclusive); excluyente con `repeticiones`
+    pub hasta: Option<chrono::NaiveDate>,
+    pub repeticiones: Option<i32>,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub num_personas: i32,
+    pub observaciones: Option<String>,
+    /// Crear la serie salteando las fechas que no se pueden reservar
+    #[serde(default)]
+    #[diesel(skip_insertion)]
+    pub omitir_conflictos: bool,
+    #[serde(default)]
+    #[diesel(skip_insertion)]
+    pub ignorar_limpieza: bool,
+}
+
+fn intervalo_por_defecto() -> i32 {
+    1
+}
+
+/// Fecha de la serie que no se pudo reservar, con el mismo error que daría
+/// `POST /reservas` para esa fecha
+#[derive(Debug, Serialize)]
+pub struct OcurrenciaRechazada {
+    pub fecha: chrono::NaiveDate,
+    #[serde(flatten)]
+    pub error: ErrorBody,
+}
+
+/// Serie con sus reservas (y, al crearla, las fechas salteadas)
+#[derive(Debug, Serialize)]
+pub struct DetalleSerie {
+    pub serie: SerieReserva,
+    pub reservas: Vec<Reserva>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub omitidas: Vec<OcurrenciaRechazada>,
+}
+
+/// ✏️ Cambios que se aplican a varias ocurrencias a la vez
+/// (la fecha de cada una no se toca: para eso, editar la reserva suelta).
+/// Con alcance `todas` también actualizan el patrón de la serie.
+#[derive(Debug, AsChangeset, Serialize, Deserialize)]
+#[diesel(table_name = series_reserva)]
+pub struct CambiosSerie {
+    pub cabana_id: Option<i32>,
+    pub hora_inicio: Option<chrono::NaiveTime>,
+    pub hora_fin: Option<chrono::NaiveTime>,
+    pub num_personas: Option<i32>,
+    pub observaciones: Option<String>,
+    #[serde(default)]
+    #[diesel(skip_update)]
+    pub ignorar_limpieza: bool,
+}
+
+impl CambiosSerie {
+    pub fn esta_vacio(&self) -> bool {
+        self.cabana_id.is_none()
+            && self.hora_inicio.is_none()
+            && self.hora_fin.is_none()
+            && self.num_personas.is_none()
+            && self.observaciones.is_none()
+    }
+}
+
+/// 🔎 A qué ocurrencias alcanza una edición o cancelación:
+///  - `alcance=una&reserva_id=N`: solo esa
+///  - `alcance=siguientes&reserva_id=N`: esa y las posteriores
+///  - `alcance=todas` (por defecto): todas las que no terminaron
+#[derive(Debug, FromForm)]
+pub struct AlcanceSerie {
+    pub alcance: Option<String>,
+    pub reserva_id: Option<i32>,
+}
+
+// =============================
+// ⏳ LISTA DE ESPERA
+// =============================
+/// Estado de una entrada de la lista de espera (tipo ENUM `estado_espera`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::EstadoEspera)]
+pub enum EstadoEspera {
+    #[serde(rename = "esperando")]
+    Esperando,
+    #[serde(rename = "ofertada")]
+    Ofertada,
+    #[serde(rename = "atendida")]
+    Atendida,
+    #[serde(rename = "cancelada")]
+    Cancelada,
+}
+
+impl EstadoEspera {
+    pub const TODOS: [EstadoEspera; 4] =
+        [EstadoEspera::Esperando, EstadoEspera::Ofertada, EstadoEspera::Atendida, EstadoEspera::Cancelada];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoEspera::Esperando => "esperando",
+            EstadoEspera::Ofertada => "ofertada",
+            EstadoEspera::Atendida => "atendida",
+            EstadoEspera::Cancelada => "cancelada",
+        }
+    }
+}
+
+/// Estado de una oferta hecha a la lista de espera (tipo ENUM `estado_oferta`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow, Serialize, Deserialize)]
+#[diesel(sql_type = crate::schema::sql_types::EstadoOferta)]
+pub enum EstadoOferta {
+    #[serde(rename = "pendiente")]
+    Pendiente,
+    #[serde(rename = "aceptada")]
+    Aceptada,
+    #[serde(rename = "rechazada")]
+    Rechazada,
+    #[serde(rename = "vencida")]
+    Vencida,
+}
+
+impl EstadoOferta {
+    pub const TODOS: [EstadoOferta; 4] =
+        [EstadoOferta::Pendiente, EstadoOferta::Aceptada, EstadoOferta::Rechazada, EstadoOferta::Vencida];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EstadoOferta::Pendiente => "pendiente",
+            EstadoOferta::Aceptada => "aceptada",
+            EstadoOferta::Rechazada => "rechazada",
+            EstadoOferta::Vencida => "vencida",
+        }
+    }
+}
+
+/// Cliente esperando que se libere un horario
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = lista_espera)]
+pub struct EntradaEspera {
+    pub id: i32,
+    pub cliente_id: i32,
+    pub fecha: chrono::NaiveDate,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    /// Instantes del horario deseado (columnas generadas, como en reservas)
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub num_personas: i32,
+    /// Cabañas que le sirven; `None` = cualquiera donde entre el grupo
+    pub cabana_ids: Option<Vec<i32>>,
+    pub observaciones: Option<String>,
+    pub estado: EstadoEspera,
+    pub fecha_creacion: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = lista_espera)]
+pub struct NewEntradaEspera {
+    pub cliente_id: i32,
+    pub fecha: chrono::NaiveDate,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub num_personas: i32,
+    pub cabana_ids: Option<Vec<i32>>,
+    pub observaciones: Option<String>,
+}
+
+/// Cabaña liberada ofrecida a una entrada de la lista, hasta `vence`
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = ofertas_espera)]
+pub struct OfertaEspera {
+    pub id: i32,
+    pub espera_id: i32,
+    pub cabana_id: i32,
+    pub estado: EstadoOferta,
+    pub vence: chrono::NaiveDateTime,
+    pub reserva_id: Option<i32>,
+    pub fecha_creacion: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = ofertas_espera)]
+pub struct NewOfertaEspera {
+    pub espera_id: i32,
+    pub cabana_id: i32,
+    pub vence: chrono::NaiveDateTime,
+}
+
+/// 🔎 Parámetros de `GET /lista-espera` y `GET /lista-espera/ofertas`
+#[derive(Debug, FromForm)]
+pub struct FiltroListaEspera {
+    pub estado: Option<String>,
+    /// Solo las de esa fecha (YYYY-MM-DD)
+    pub fecha: Option<String>,
+}
+
+// =============================
+// ⏱️ BLOQUEOS TEMPORALES
+// =============================
+/// Horario retenido mientras se toman los datos del cliente; ocupa la cabaña hasta `vence`
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = bloqueos)]
+pub struct Bloqueo {
+    pub id: i32,
+    pub cabana_id: i32,
+    pub fecha: chrono::NaiveDate,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub inicio: chrono::NaiveDateTime,
+    pub fin: chrono::NaiveDateTime,
+    pub vence: chrono::NaiveDateTime,
+    pub nota: Option<String>,
+    pub fecha_creacion: chrono::NaiveDateTime,
+    /// Oferta de la lista de espera que retiene este horario (vence con ella)
+    pub oferta_id: Option<i32>,
+}
+
+/// El vencimiento lo fija el servidor con `configuracion.minutos_bloqueo`
+#[derive(Debug, Insertable, Serialize, Deserialize)]
+#[diesel(table_name = bloqueos)]
+pub struct NewBloqueo {
+    pub cabana_id: i32,
+    pub fecha: chrono::NaiveDate,
+    pub hora_inicio: chrono::NaiveTime,
+    pub hora_fin: chrono::NaiveTime,
+    pub nota: Option<String>,
+}
+
+/// Datos del cliente para convertir un bloqueo en reserva
+#[derive(Debug, Deserialize)]
+pub struct ConfirmarBloqueo {
+    pub cliente_id: i32,
+    pub num_personas: i32,
     pub observaciones: Option<String>,
+    pub codigo_promocion: Option<String>,
+    #[serde(default)]
+    pub ignorar_limpieza: bool,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn los_estados_finales_no_tienen_transiciones() {
+        for estado in EstadoReserva::TODOS {
+            assert_eq!(estado.es_final(), estado.transiciones().is_empty(), "{}", estado);
+        }
+    }
+
+    #[test]
+    fn ningun_estado_pasa_a_si_mismo_ni_vuelve_a_pendiente() {
+        for estado in EstadoReserva::TODOS {
+            assert!(!estado.puede_pasar_a(estado), "{}", estado);
+            assert!(!estado.puede_pasar_a(EstadoReserva::Pendiente), "{}", estado);
+        }
+    }
+
+    #[test]
+    fn en_curso_solo_puede_completarse() {
+        assert_eq!(EstadoReserva::EnCurso.transiciones(), &[EstadoReserva::Completada]);
+        assert!(!EstadoReserva::Pendiente.puede_pasar_a(EstadoReserva::Completada));
+        assert!(!EstadoReserva::Confirmada.puede_pasar_a(EstadoReserva::Completada));
+    }
+
+    #[test]
+    fn los_estados_abiertos_pueden_cancelarse_o_marcarse_no_show() {
+        for estado in [EstadoReserva::Pendiente, EstadoReserva::Confirmada] {
+            assert!(estado.puede_pasar_a(EstadoReserva::Cancelada), "{}", estado);
+            assert!(estado.puede_pasar_a(EstadoReserva::NoShow), "{}", estado);
+            assert!(estado.puede_pasar_a(EstadoReserva::EnCurso), "{}", estado);
+        }
+    }
+
+    #[test]
+    fn los_estados_iniciales_no_son_finales() {
+        for estado in EstadoReserva::INICIALES {
+            assert!(!estado.es_final(), "{}", estado);
+        }
+    }
+
+    #[test]
+    fn minutos_limpieza_distingue_ausente_de_null() {
+        let ausente: UpdateCabana = serde_json::from_str(r#"{"nombre":"VIP"}"#).unwrap();
+        assert_eq!(ausente.minutos_limpieza, None);
+
+        let nulo: UpdateCabana = serde_json::from_str(r#"{"minutos_limpieza":null}"#).unwrap();
+        assert_eq!(nulo.minutos_limpieza, Some(None));
+        assert!(!nulo.esta_vacio());
+
+        let valor: UpdateCabana = serde_json::from_str(r#"{"minutos_limpieza":20}"#).unwrap();
+        assert_eq!(valor.minutos_limpieza, Some(Some(20)));
+    }
+}
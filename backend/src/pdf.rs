@@ -0,0 +1,223 @@
+/// 📄 Generador mínimo de PDF de solo texto
+///
+/// Usa las fuentes estándar Courier / Courier-Bold (todo visor PDF las trae,
+/// no hay que embeber nada) con codificación WinAnsi, así que cubre tildes y ñ
+/// pero no emojis. Al ser monoespaciada, las columnas se alinean con `format!`.
+/// Pagina solo cuando se acaba la hoja.
+pub struct DocumentoPdf {
+    lineas: Vec<Linea>,
+}
+
+struct Linea {
+    texto: String,
+    negrita: bool,
+    tamano: u32,
+}
+
+// Hoja A4 en puntos
+const ANCHO: u32 = 595;
+const ALTO: u32 = 842;
+const MARGEN: u32 = 50;
+const TAMANO_NORMAL: u32 = 10;
+const TAMANO_TITULO: u32 = 14;
+
+/// Caracteres por línea con la fuente normal (Courier: 0,6 × tamaño por carácter)
+pub const COLUMNAS: usize = ((ANCHO - 2 * MARGEN) * 10 / (6 * TAMANO_NORMAL)) as usize;
+
+impl DocumentoPdf {
+    pub fn new() -> Self {
+        DocumentoPdf { lineas: Vec::new() }
+    }
+
+    pub fn titulo(&mut self, texto: &str) -> &mut Self {
+        self.agregar(texto, true, TAMANO_TITULO)
+    }
+
+    pub fn negrita(&mut self, texto: &str) -> &mut Self {
+        self.agregar(texto, true, TAMANO_NORMAL)
+    }
+
+    pub fn linea(&mut self, texto: &str) -> &mut Self {
+        self.agregar(texto, false, TAMANO_NORMAL)
+    }
+
+    pub fn separador(&mut self) -> &mut Self {
+        self.linea(&"-".repeat(COLUMNAS))
+    }
+
+    fn agregar(&mut self, texto: &str, negrita: bool, tamano: u32) -> &mut Self {
+        self.lineas.push(Linea { texto: texto.to_string(), negrita, tamano });
+        self
+    }
+
+    /// Arma el archivo: catálogo, árbol de páginas, fuentes, una página y un
+    /// contenido por hoja y, al final, la tabla xref con los offsets de cada objeto
+    pub fn renderizar(&self) -> Vec<u8> {
+        let paginas = self.paginar();
+        let mut objetos: Vec<Vec<u8>> = Vec::new();
+
+        // 1 catálogo, 2 páginas, 3 y 4 fuentes; luego pares (página, contenido)
+        let kids: Vec<String> = (0..paginas.len()).map(|i| format!("{} 0 R", 5 + 2 * i)).collect();
+        objetos.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objetos.push(
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), paginas.len()).into_bytes(),
+        );
+        objetos.push(fuente("Courier"));
+        objetos.push(fuente("Courier-Bold"));
+
+        for (i, contenido) in paginas.iter().enumerate() {
+            objetos.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    ANCHO,
+                    ALTO,
+                    6 + 2 * i
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", contenido.len()).into_bytes();
+            stream.extend_from_slice(contenido);
+            stream.extend_from_slice(b"\nendstream");
+            objetos.push(stream);
+        }
+
+        let mut salida = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objetos.len());
+        for (i, obj) in objetos.iter().enumerate() {
+            offsets.push(salida.len());
+            salida.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            salida.extend_from_slice(obj);
+            salida.extend_from_slice(b"\nendobj\n");
+        }
+
+        let inicio_xref = salida.len();
+        salida.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objetos.len() + 1).as_bytes());
+        for offset in offsets {
+            salida.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        salida.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objetos.len() + 1,
+                inicio_xref
+            )
+            .as_bytes(),
+        );
+        salida
+    }
+
+    /// Reparte las líneas en hojas y devuelve el flujo de contenido de cada una
+    fn paginar(&self) -> Vec<Vec<u8>> {
+        let mut paginas = Vec::new();
+        let mut actual: Vec<u8> = Vec::new();
+        let mut y = ALTO - MARGEN;
+
+        for linea in &self.lineas {
+            let alto_linea = linea.tamano + linea.tamano / 2;
+            if y < MARGEN + alto_linea && !actual.is_empty() {
+                paginas.push(std::mem::take(&mut actual));
+                y = ALTO - MARGEN;
+            }
+            y -= alto_linea;
+
+            let fuente = if linea.negrita { "F2" } else { "F1" };
+            actual.extend_from_slice(format!("BT /{} {} Tf {} {} Td (", fuente, linea.tamano, MARGEN, y).as_bytes());
+            actual.extend(codificar(&linea.texto));
+            actual.extend_from_slice(b") Tj ET\n");
+        }
+
+        paginas.push(actual);
+        paginas
+    }
+}
+
+fn fuente(nombre: &str) -> Vec<u8> {
+    format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", nombre).into_bytes()
+}
+
+/// Texto → bytes WinAnsi escapados para un literal `( ... )`.
+/// Latin-1 coincide con WinAnsi en lo que usamos; lo demás se cambia por '?'
+fn codificar(texto: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(texto.len());
+    for c in texto.chars() {
+        let b = match c {
+            '–' | '—' => b'-',
+            c if (c as u32) < 0x20 => b' ',
+            c if (c as u32) <= 0xFF => c as u8,
+            _ => b'?',
+        };
+        if matches!(b, b'(' | b')' | b'\\') {
+            bytes.push(b'\\');
+        }
+        bytes.push(b);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codificar_escapa_los_delimitadores_del_literal() {
+        assert_eq!(codificar(r"Total (IVA) \ 13%"), br"Total \(IVA\) \\ 13%".to_vec());
+    }
+
+    #[test]
+    fn codificar_usa_latin1_y_reemplaza_lo_demas() {
+        assert_eq!(codificar("Cabaña"), b"Caba\xF1a".to_vec());
+        assert_eq!(codificar("19:00–21:00 — ok"), b"19:00-21:00 - ok".to_vec());
+        assert_eq!(codificar("Fiesta 🎉"), b"Fiesta ?".to_vec());
+        assert_eq!(codificar("a\tb\nc"), b"a b c".to_vec());
+    }
+
+    #[test]
+    fn paginar_entra_en_una_hoja_si_hay_pocas_lineas() {
+        let mut doc = DocumentoPdf::new();
+        doc.titulo("Comprobante").linea("Cabaña 1");
+        let paginas = doc.paginar();
+
+        assert_eq!(paginas.len(), 1);
+        let contenido = String::from_utf8_lossy(&paginas[0]);
+        assert!(contenido.starts_with(&format!("BT /F2 {} Tf {} ", TAMANO_TITULO, MARGEN)));
+        assert_eq!(contenido.matches("Tj ET").count(), 2);
+    }
+
+    #[test]
+    fn paginar_abre_otra_hoja_al_llegar_al_margen() {
+        let alto_linea = TAMANO_NORMAL + TAMANO_NORMAL / 2;
+        let por_hoja = ((ALTO - 2 * MARGEN) / alto_linea) as usize;
+
+        let mut doc = DocumentoPdf::new();
+        for i in 0..por_hoja {
+            doc.linea(&format!("Línea {}", i));
+        }
+        assert_eq!(doc.paginar().len(), 1);
+
+        doc.linea("Una más");
+        let paginas = doc.paginar();
+        assert_eq!(paginas.len(), 2);
+        assert_eq!(String::from_utf8_lossy(&paginas[1]).matches("Tj ET").count(), 1);
+    }
+
+    #[test]
+    fn documento_vacio_tiene_una_hoja() {
+        assert_eq!(DocumentoPdf::new().paginar().len(), 1);
+    }
+
+    #[test]
+    fn renderizar_arma_un_pdf_completo() {
+        let mut doc = DocumentoPdf::new();
+        doc.linea("Hola");
+        let pdf = doc.renderizar();
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let texto = String::from_utf8_lossy(&pdf);
+        assert!(texto.contains("/Count 1"));
+        // El offset de startxref apunta a la tabla xref
+        let inicio_xref: usize = texto.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[inicio_xref..].starts_with(b"xref\n"));
+    }
+}
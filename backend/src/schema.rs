@@ -1,6 +1,75 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_cabana"))]
+    pub struct EstadoCabana;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_espera"))]
+    pub struct EstadoEspera;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_oferta"))]
+    pub struct EstadoOferta;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "estado_reserva"))]
+    pub struct EstadoReserva;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "frecuencia_serie"))]
+    pub struct FrecuenciaSerie;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "metodo_pago"))]
+    pub struct MetodoPago;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "modo_regla_precio"))]
+    pub struct ModoReglaPrecio;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tipo_descuento"))]
+    pub struct TipoDescuento;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tipo_pago"))]
+    pub struct TipoPago;
+}
+
+diesel::table! {
+    bloqueos (id) {
+        id -> Int4,
+        cabana_id -> Int4,
+        fecha -> Date,
+        hora_inicio -> Time,
+        hora_fin -> Time,
+        inicio -> Timestamp,
+        fin -> Timestamp,
+        vence -> Timestamp,
+        #[max_length = 200]
+        nota -> Nullable<Varchar>,
+        fecha_creacion -> Timestamp,
+        oferta_id -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
+    canjes_promocion (id) {
+        id -> Int4,
+        promocion_id -> Int4,
+        reserva_id -> Int4,
+        cliente_id -> Int4,
+        descuento -> Numeric,
+        fecha_canje -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoCabana;
+
     cabanas (id) {
         id -> Int4,
         #[max_length = 50]
@@ -8,10 +77,14 @@ diesel::table! {
         capacidad -> Int4,
         #[max_length = 100]
         ubicacion -> Nullable<Varchar>,
-        #[max_length = 20]
-        estado -> Varchar,
+        estado -> EstadoCabana,
         descripcion -> Nullable<Text>,
         precio_hora -> Nullable<Numeric>,
+        archivada -> Bool,
+        ocupacion_minima -> Int4,
+        minutos_limpieza -> Nullable<Int4>,
+        #[max_length = 100]
+        zona -> Nullable<Varchar>,
     }
 }
 
@@ -27,10 +100,205 @@ diesel::table! {
         #[max_length = 30]
         dni -> Nullable<Varchar>,
         fecha_registro -> Nullable<Timestamp>,
+        #[max_length = 20]
+        nit -> Nullable<Varchar>,
+        #[max_length = 150]
+        razon_social -> Nullable<Varchar>,
+        no_shows -> Int4,
+    }
+}
+
+diesel::table! {
+    comprobantes (id) {
+        id -> Int4,
+        numero -> Int4,
+        reserva_id -> Int4,
+        fecha_emision -> Timestamp,
+        #[max_length = 100]
+        nombre_cliente -> Varchar,
+        #[max_length = 20]
+        nit -> Nullable<Varchar>,
+        #[max_length = 150]
+        razon_social -> Nullable<Varchar>,
+        descripcion -> Text,
+        base_imponible -> Numeric,
+        tasa_impuesto -> Numeric,
+        impuesto -> Numeric,
+        total -> Numeric,
+    }
+}
+
+diesel::table! {
+    configuracion (id) {
+        id -> Bool,
+        minutos_limpieza -> Int4,
+        minutos_oferta_espera -> Int4,
+        minutos_bloqueo -> Int4,
+        minutos_tolerancia_no_show -> Int4,
+        tasa_iva -> Numeric,
+    }
+}
+
+diesel::table! {
+    excepciones_horario (fecha) {
+        fecha -> Date,
+        cerrado -> Bool,
+        abre -> Nullable<Time>,
+        cierra -> Nullable<Time>,
+        #[max_length = 150]
+        motivo -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    feriados (fecha) {
+        fecha -> Date,
+        #[max_length = 100]
+        nombre -> Varchar,
+    }
+}
+
+diesel::table! {
+    horarios_atencion (dia_semana) {
+        dia_semana -> Int4,
+        abre -> Nullable<Time>,
+        cierra -> Nullable<Time>,
+        cerrado -> Bool,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoEspera;
+
+    lista_espera (id) {
+        id -> Int4,
+        cliente_id -> Int4,
+        fecha -> Date,
+        hora_inicio -> Time,
+        hora_fin -> Time,
+        inicio -> Timestamp,
+        fin -> Timestamp,
+        num_personas -> Int4,
+        cabana_ids -> Nullable<Array<Int4>>,
+        observaciones -> Nullable<Text>,
+        estado -> EstadoEspera,
+        fecha_creacion -> Timestamp,
+    }
+}
+
+diesel::table! {
+    mantenimientos (id) {
+        id -> Int4,
+        cabana_id -> Int4,
+        inicio -> Timestamp,
+        fin -> Timestamp,
+        #[max_length = 200]
+        motivo -> Varchar,
+        fecha_creacion -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoOferta;
+
+    ofertas_espera (id) {
+        id -> Int4,
+        espera_id -> Int4,
+        cabana_id -> Int4,
+        estado -> EstadoOferta,
+        vence -> Timestamp,
+        reserva_id -> Nullable<Int4>,
+        fecha_creacion -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TipoPago;
+    use super::sql_types::MetodoPago;
+
+    pagos (id) {
+        id -> Int4,
+        reserva_id -> Int4,
+        tipo -> TipoPago,
+        monto -> Numeric,
+        metodo -> MetodoPago,
+        #[max_length = 100]
+        referencia -> Nullable<Varchar>,
+        #[max_length = 100]
+        registrado_por -> Varchar,
+        fecha_pago -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TipoDescuento;
+
+    promociones (id) {
+        id -> Int4,
+        #[max_length = 30]
+        codigo -> Varchar,
+        descripcion -> Nullable<Text>,
+        tipo -> TipoDescuento,
+        valor -> Numeric,
+        vigente_desde -> Nullable<Date>,
+        vigente_hasta -> Nullable<Date>,
+        dias_semana -> Nullable<Array<Int4>>,
+        hora_desde -> Nullable<Time>,
+        hora_hasta -> Nullable<Time>,
+        cabana_ids -> Nullable<Array<Int4>>,
+        usos_maximos -> Nullable<Int4>,
+        usos_por_cliente -> Nullable<Int4>,
+        horas_minimas -> Nullable<Numeric>,
+        activa -> Bool,
+        fecha_creacion -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ModoReglaPrecio;
+
+    reglas_precio (id) {
+        id -> Int4,
+        #[max_length = 100]
+        nombre -> Varchar,
+        cabana_id -> Nullable<Int4>,
+        #[max_length = 100]
+        zona -> Nullable<Varchar>,
+        dias_semana -> Nullable<Array<Int4>>,
+        hora_desde -> Nullable<Time>,
+        hora_hasta -> Nullable<Time>,
+        fecha_desde -> Nullable<Date>,
+        fecha_hasta -> Nullable<Date>,
+        solo_feriados -> Bool,
+        modo -> ModoReglaPrecio,
+        valor -> Numeric,
+        prioridad -> Int4,
+        activa -> Bool,
+    }
+}
+
+diesel::table! {
+    reglas_reserva (id) {
+        id -> Int4,
+        cabana_id -> Nullable<Int4>,
+        dias_semana -> Nullable<Array<Int4>>,
+        duracion_minima -> Nullable<Int4>,
+        duracion_maxima -> Nullable<Int4>,
+        granularidad -> Nullable<Int4>,
+        anticipacion_minima -> Nullable<Int4>,
+        anticipacion_maxima_dias -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::EstadoReserva;
+
     reservas (id) {
         id -> Int4,
         cliente_id -> Int4,
@@ -38,14 +306,80 @@ diesel::table! {
         fecha_reserva -> Date,
         hora_inicio -> Time,
         hora_fin -> Time,
-        #[max_length = 20]
-        estado -> Varchar,
+        estado -> EstadoReserva,
         observaciones -> Nullable<Text>,
         fecha_creacion -> Nullable<Timestamp>,
+        inicio -> Timestamp,
+        fin -> Timestamp,
+        num_personas -> Int4,
+        monto_total -> Nullable<Numeric>,
+        monto_pagado -> Numeric,
+        saldo -> Nullable<Numeric>,
+        descuento -> Numeric,
+        serie_id -> Nullable<Int4>,
+        llegada -> Nullable<Timestamp>,
+        salida -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::FrecuenciaSerie;
+
+    series_reserva (id) {
+        id -> Int4,
+        cliente_id -> Int4,
+        cabana_id -> Int4,
+        frecuencia -> FrecuenciaSerie,
+        intervalo -> Int4,
+        fecha_inicio -> Date,
+        hasta -> Nullable<Date>,
+        repeticiones -> Nullable<Int4>,
+        hora_inicio -> Time,
+        hora_fin -> Time,
+        num_personas -> Int4,
+        observaciones -> Nullable<Text>,
+        fecha_creacion -> Timestamp,
     }
 }
 
+diesel::joinable!(bloqueos -> cabanas (cabana_id));
+diesel::joinable!(bloqueos -> ofertas_espera (oferta_id));
+diesel::joinable!(canjes_promocion -> clientes (cliente_id));
+diesel::joinable!(canjes_promocion -> promociones (promocion_id));
+diesel::joinable!(canjes_promocion -> reservas (reserva_id));
+diesel::joinable!(comprobantes -> reservas (reserva_id));
+diesel::joinable!(lista_espera -> clientes (cliente_id));
+diesel::joinable!(mantenimientos -> cabanas (cabana_id));
+diesel::joinable!(ofertas_espera -> cabanas (cabana_id));
+diesel::joinable!(ofertas_espera -> lista_espera (espera_id));
+diesel::joinable!(ofertas_espera -> reservas (reserva_id));
+diesel::joinable!(pagos -> reservas (reserva_id));
+diesel::joinable!(reglas_precio -> cabanas (cabana_id));
+diesel::joinable!(reglas_reserva -> cabanas (cabana_id));
 diesel::joinable!(reservas -> cabanas (cabana_id));
 diesel::joinable!(reservas -> clientes (cliente_id));
+diesel::joinable!(reservas -> series_reserva (serie_id));
+diesel::joinable!(series_reserva -> cabanas (cabana_id));
+diesel::joinable!(series_reserva -> clientes (cliente_id));
 
-diesel::allow_tables_to_appear_in_same_query!(cabanas, clientes, reservas,);
+diesel::allow_tables_to_appear_in_same_query!(
+    bloqueos,
+    cabanas,
+    canjes_promocion,
+    clientes,
+    comprobantes,
+    configuracion,
+    excepciones_horario,
+    feriados,
+    horarios_atencion,
+    lista_espera,
+    mantenimientos,
+    ofertas_espera,
+    pagos,
+    promociones,
+    reglas_precio,
+    reglas_reserva,
+    reservas,
+    series_reserva,
+);
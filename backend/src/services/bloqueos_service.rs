@@ -0,0 +1,164 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{Bloqueo, ConfirmarBloqueo, EstadoReserva, NewBloqueo, NewReserva, OfertaEspera, Reserva};
+use crate::schema::bloqueos;
+
+// =============================
+// ⏱️ Bloqueos temporales
+//  - Retienen un horario mientras el anfitrión toma los datos del cliente
+//  - Hasta `vence` cuentan como ocupados en `validar_conflictos` y en la
+//    disponibilidad; al vencer o liberarse, el hueco pasa a la lista de espera
+//  - Cada oferta pendiente de la lista de espera retiene su horario con uno
+//    propio (`oferta_id`), que vive y muere con la oferta
+// =============================
+pub fn listar(conn: &mut PgConnection) -> QueryResult<Vec<Bloqueo>> {
+    bloqueos::table
+        .filter(bloqueos::vence.gt(Local::now().naive_local()))
+        .order((bloqueos::inicio.asc(), bloqueos::id.asc()))
+        .load::<Bloqueo>(conn)
+}
+
+/// Pasa por las mismas validaciones que una reserva, salvo las del cliente
+/// (capacidad, promociones), que se controlan al confirmar
+pub fn crear(conn: &mut PgConnection, nuevo: NewBloqueo) -> ApiResult<Bloqueo> {
+    use crate::services::{
+        cabanas_service, configuracion_service, horarios_service, mantenimientos_service, reglas_reserva_service,
+        validaciones_service,
+    };
+
+    conn.transaction::<Bloqueo, ApiError, _>(|conn| {
+        // Mismo lock que `crear_reserva`: sin él, una reserva y un bloqueo del
+        // mismo horario podrían pasar `validar_conflictos` a la vez
+        let cabana = cabanas_service::bloquear_cabana_activa(conn, nuevo.cabana_id)?;
+
+        validaciones_service::validar_horas(nuevo.hora_inicio, nuevo.hora_fin)?;
+
+        let (inicio, fin) = validaciones_service::rango_reserva(nuevo.fecha, nuevo.hora_inicio, nuevo.hora_fin);
+        reglas_reserva_service::validar_reserva(conn, cabana.id, nuevo.fecha, inicio, fin, true, true)?;
+        horarios_service::validar_en_horario(conn, inicio, fin)?;
+        mantenimientos_service::validar_fuera_de_mantenimiento(conn, cabana.id, inicio, fin)?;
+        validaciones_service::validar_conflictos(conn, &cabana, inicio, fin, None, false)?;
+
+        let minutos = configuracion_service::obtener(conn)?.minutos_bloqueo;
+        let vence = Local::now().naive_local() + Duration::minutes(minutos as i64);
+        Ok(retener(conn, &nuevo, vence, None)?)
+    })
+}
+
+/// Inserta el bloqueo ya validado. También lo usa la lista de espera para
+/// que una oferta pendiente retenga su horario (`oferta_id`)
+pub fn retener(
+    conn: &mut PgConnection,
+    nuevo: &NewBloqueo,
+    vence: NaiveDateTime,
+    oferta_id: Option<i32>,
+) -> QueryResult<Bloqueo> {
+    // Los vencidos que el actualizador todavía no borró no deben chocar
+    // con la restricción `bloqueos_sin_solapes`
+    diesel::delete(
+        bloqueos::table
+            .filter(bloqueos::cabana_id.eq(nuevo.cabana_id))
+            .filter(bloqueos::vence.le(Local::now().naive_local())),
+    )
+    .execute(conn)?;
+
+    diesel::insert_into(bloqueos::table)
+        .values((nuevo, bloqueos::vence.eq(vence), bloqueos::oferta_id.eq(oferta_id)))
+        .get_result::<Bloqueo>(conn)
+}
+
+/// Suelta el horario que retenía una oferta (al cerrarse o aceptarse)
+pub fn soltar_oferta(conn: &mut PgConnection, oferta_id: i32) -> QueryResult<usize> {
+    diesel::delete(bloqueos::table.filter(bloqueos::oferta_id.eq(oferta_id))).execute(conn)
+}
+
+// =============================
+// ✅ Confirmar como reserva
+//  - El bloqueo se borra y la reserva se crea en la misma transacción:
+//    si `crear_reserva` falla, el bloqueo sigue en pie
+//  - Anticipación y granularidad se controlaron al bloquear: los minutos al
+//    teléfono no deben hacer caer un horario que era válido
+// =============================
+pub fn confirmar(conn: &mut PgConnection, bloqueo_id: i32, datos: ConfirmarBloqueo) -> ApiResult<Reserva> {
+    use crate::services::reservas_service;
+
+    conn.transaction::<Reserva, ApiError, _>(|conn| {
+        let bloqueo = vigente(conn, bloqueo_id)?;
+        diesel::delete(bloqueos::table.find(bloqueo.id)).execute(conn)?;
+
+        reservas_service::crear_reserva(
+            conn,
+            NewReserva {
+                cliente_id: datos.cliente_id,
+                cabana_id: bloqueo.cabana_id,
+                fecha_reserva: bloqueo.fecha,
+                hora_inicio: bloqueo.hora_inicio,
+                hora_fin: bloqueo.hora_fin,
+                estado: EstadoReserva::default(),
+                observaciones: datos.observaciones,
+                num_personas: datos.num_personas,
+                codigo_promocion: datos.codigo_promocion,
+                ignorar_limpieza: datos.ignorar_limpieza,
+                serie_id: None,
+            },
+            true,
+        )
+    })
+}
+
+/// ❌ Libera el horario antes de tiempo; devuelve las ofertas a la lista de espera
+pub fn liberar(conn: &mut PgConnection, bloqueo_id: i32) -> ApiResult<Vec<OfertaEspera>> {
+    use crate::services::lista_espera_service;
+
+    conn.transaction::<Vec<OfertaEspera>, ApiError, _>(|conn| {
+        let bloqueo = vigente(conn, bloqueo_id)?;
+        diesel::delete(bloqueos::table.find(bloqueo.id)).execute(conn)?;
+        lista_espera_service::ofrecer_hueco(conn, bloqueo.cabana_id, bloqueo.inicio, bloqueo.fin)
+    })
+}
+
+// =============================
+// 🕒 Limpieza de vencidos (lo llama el actualizador en segundo plano)
+//  - Devuelve los bloqueos borrados y las ofertas que generaron sus huecos
+//  - Los de ofertas los suelta `vencer_ofertas` al cerrar la oferta
+// =============================
+pub fn vencer_bloqueos(conn: &mut PgConnection) -> ApiResult<(Vec<Bloqueo>, Vec<OfertaEspera>)> {
+    use crate::services::lista_espera_service;
+
+    conn.transaction::<_, ApiError, _>(|conn| {
+        let vencidos = diesel::delete(
+            bloqueos::table
+                .filter(bloqueos::vence.le(Local::now().naive_local()))
+                .filter(bloqueos::oferta_id.is_null()),
+        )
+        .get_results::<Bloqueo>(conn)?;
+
+        let mut ofertas = Vec::new();
+        for b in &vencidos {
+            ofertas.extend(lista_espera_service::ofrecer_hueco(conn, b.cabana_id, b.inicio, b.fin)?);
+        }
+        Ok((vencidos, ofertas))
+    })
+}
+
+/// 404 si no existe o ya venció (aunque el actualizador todavía no lo haya borrado);
+/// 409 si retiene una oferta de la lista de espera, que se gestiona desde la oferta
+fn vigente(conn: &mut PgConnection, bloqueo_id: i32) -> ApiResult<Bloqueo> {
+    let bloqueo = bloqueos::table
+        .find(bloqueo_id)
+        .filter(bloqueos::vence.gt(Local::now().naive_local()))
+        .for_update()
+        .first::<Bloqueo>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Bloqueo {} no encontrado o vencido", bloqueo_id)))?;
+
+    if let Some(oferta_id) = bloqueo.oferta_id {
+        return Err(ApiError::conflicto(format!(
+            "El bloqueo {} retiene la oferta {} de la lista de espera; se acepta o rechaza desde la oferta",
+            bloqueo.id, oferta_id
+        ))
+        .con_detalles(serde_json::json!({ "oferta_id": oferta_id })));
+    }
+    Ok(bloqueo)
+}
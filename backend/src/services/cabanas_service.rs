@@ -1,12 +1,73 @@
+use chrono::{Local, NaiveDateTime};
 use diesel::prelude::*;
-use crate::models::{Cabana, NewCabana};
-use crate::schema::cabanas;
+use std::collections::HashMap;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    Cabana, CabanaConOcupacion, EstadoCabana, EstadoOcupacion, EstadoReserva, Mantenimiento, NewCabana,
+    OcupacionActual, Reserva, UpdateCabana,
+};
+use crate::schema::{bloqueos, cabanas, mantenimientos, reservas};
 
-pub fn listar_cabanas(conn: &mut PgConnection) -> QueryResult<Vec<Cabana>> {
+pub fn listar_cabanas(conn: &mut PgConnection, incluir_archivadas: bool) -> QueryResult<Vec<Cabana>> {
     // Ordenadas por ID ascendente para UI
-    cabanas::table
+    let mut query = cabanas::table
         .order(cabanas::id.asc())
-        .load::<Cabana>(conn)
+        .into_boxed();
+
+    if !incluir_archivadas {
+        query = query.filter(cabanas::archivada.eq(false));
+    }
+
+    query.load::<Cabana>(conn)
+}
+
+pub fn obtener_cabana(conn: &mut PgConnection, cabana_id: i32) -> ApiResult<Cabana> {
+    cabanas::table
+        .find(cabana_id)
+        .first::<Cabana>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cabaña {} no encontrada", cabana_id)))
+}
+
+/// Igual que `obtener_cabana`, pero falla si la cabaña está archivada o
+/// fuera de servicio (no se puede reservar ni modificar su operación)
+pub fn obtener_cabana_activa(conn: &mut PgConnection, cabana_id: i32) -> ApiResult<Cabana> {
+    activa(obtener_cabana(conn, cabana_id)?)
+}
+
+/// `obtener_cabana` con la fila bloqueada (`FOR UPDATE`) hasta el fin de la
+/// transacción. Reservas y bloqueos viven en tablas distintas y ninguna
+/// restricción EXCLUDE los cruza: quien vaya a ocupar un horario toma este
+/// lock antes de buscar conflictos, así dos altas en la misma cabaña no
+/// pasan el chequeo a la vez
+pub fn bloquear_cabana(conn: &mut PgConnection, cabana_id: i32) -> ApiResult<Cabana> {
+    cabanas::table
+        .find(cabana_id)
+        .for_update()
+        .first::<Cabana>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cabaña {} no encontrada", cabana_id)))
+}
+
+/// `bloquear_cabana` + las comprobaciones de `obtener_cabana_activa`
+pub fn bloquear_cabana_activa(conn: &mut PgConnection, cabana_id: i32) -> ApiResult<Cabana> {
+    activa(bloquear_cabana(conn, cabana_id)?)
+}
+
+fn activa(cabana: Cabana) -> ApiResult<Cabana> {
+    if cabana.archivada {
+        return Err(ApiError::conflicto(format!(
+            "La cabaña {} está archivada y no admite reservas",
+            cabana.nombre
+        )));
+    }
+    if cabana.estado == EstadoCabana::FueraDeServicio {
+        return Err(ApiError::conflicto(format!(
+            "La cabaña {} está fuera de servicio y no admite reservas",
+            cabana.nombre
+        )));
+    }
+    Ok(cabana)
 }
 
 pub fn crear_cabana(conn: &mut PgConnection, nueva: NewCabana) -> QueryResult<Cabana> {
@@ -15,12 +76,156 @@ pub fn crear_cabana(conn: &mut PgConnection, nueva: NewCabana) -> QueryResult<Ca
         .get_result::<Cabana>(conn)
 }
 
+pub fn actualizar_cabana(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    cambios: UpdateCabana,
+) -> ApiResult<Cabana> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+    if matches!(cambios.capacidad, Some(c) if c < 1) {
+        return Err(ApiError::validacion("La capacidad debe ser al menos 1")
+            .con_detalles(serde_json::json!({ "campo": "capacidad" })));
+    }
+    if matches!(cambios.ocupacion_minima, Some(m) if m < 1) {
+        return Err(ApiError::validacion("La ocupación mínima debe ser al menos 1")
+            .con_detalles(serde_json::json!({ "campo": "ocupacion_minima" })));
+    }
+
+    diesel::update(cabanas::table.find(cabana_id))
+        .set(&cambios)
+        .get_result::<Cabana>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cabaña {} no encontrada", cabana_id)))
+}
+
+/// Estado administrativo; la ocupación no se fija a mano (ver `con_ocupacion`)
 pub fn actualizar_estado(
     conn: &mut PgConnection,
     cabana_id: i32,
-    nuevo_estado: &str,
-) -> QueryResult<Cabana> {
+    nuevo_estado: EstadoCabana,
+) -> ApiResult<Cabana> {
     diesel::update(cabanas::table.find(cabana_id))
         .set(cabanas::estado.eq(nuevo_estado))
         .get_result::<Cabana>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cabaña {} no encontrada", cabana_id)))
+}
+
+// =============================
+// 📦 Archivar cabaña
+//  - Deja de estar disponible para reservar
+//  - Sus reservas pasadas siguen consultables
+//  - Se rechaza si aún tiene reservas activas sin terminar o bloqueos vigentes
+// =============================
+pub fn archivar_cabana(conn: &mut PgConnection, cabana_id: i32) -> ApiResult<Cabana> {
+    conn.transaction::<Cabana, ApiError, _>(|conn| {
+        let cabana = bloquear_cabana(conn, cabana_id)?;
+        let ahora = Local::now().naive_local();
+
+        // Por `fin` y no por fecha: una reserva de anoche que cruza la medianoche sigue abierta
+        let pendientes = reservas::table
+            .filter(reservas::cabana_id.eq(cabana.id))
+            .filter(reservas::fin.gt(ahora))
+            .filter(reservas::estado.ne_all(EstadoReserva::FINALES))
+            .select(reservas::id)
+            .load::<i32>(conn)?;
+
+        if !pendientes.is_empty() {
+            return Err(ApiError::conflicto(format!(
+                "La cabaña {} tiene reservas pendientes; muévelas o cancélalas antes de archivarla",
+                cabana.nombre
+            ))
+            .con_detalles(serde_json::json!({ "reservas": pendientes })));
+        }
+
+        let retenidos = bloqueos::table
+            .filter(bloqueos::cabana_id.eq(cabana.id))
+            .filter(bloqueos::vence.gt(ahora))
+            .select(bloqueos::id)
+            .load::<i32>(conn)?;
+
+        if !retenidos.is_empty() {
+            return Err(ApiError::conflicto(format!(
+                "La cabaña {} tiene horarios retenidos; libéralos o espera a que venzan antes de archivarla",
+                cabana.nombre
+            ))
+            .con_detalles(serde_json::json!({ "bloqueos": retenidos })));
+        }
+
+        Ok(diesel::update(cabanas::table.find(cabana.id))
+            .set(cabanas::archivada.eq(true))
+            .get_result::<Cabana>(conn)?)
+    })
+}
+
+// =============================
+// 🔴 Ocupación en vivo
+//  - Se calcula al leer a partir de reservas y mantenimientos
+//  - Un mantenimiento en curso manda sobre una reserva que lo pise
+//  - Si hubiera dos en curso a la vez, se informa la que empezó (o termina) más tarde
+//  - Tres consultas para todo el listado, no tres por cabaña
+// =============================
+pub fn con_ocupacion(conn: &mut PgConnection, lista: Vec<Cabana>) -> QueryResult<Vec<CabanaConOcupacion>> {
+    let ahora: NaiveDateTime = Local::now().naive_local();
+    let ids: Vec<i32> = lista.iter().map(|c| c.id).collect();
+
+    let activas = || {
+        reservas::table
+            .filter(reservas::cabana_id.eq_any(ids.clone()))
+            .filter(reservas::estado.ne_all(EstadoReserva::FINALES))
+    };
+
+    // Con check-in anticipado la reserva ya ocupa la cabaña antes de su inicio
+    let mut actuales: HashMap<i32, Reserva> = activas()
+        .filter(reservas::inicio.le(ahora).or(reservas::estado.eq(EstadoReserva::EnCurso)))
+        .filter(reservas::fin.gt(ahora))
+        .order(reservas::inicio.asc())
+        .load::<Reserva>(conn)?
+        .into_iter()
+        .map(|r| (r.cabana_id, r))
+        .collect();
+
+    let mut proximas: HashMap<i32, Reserva> = activas()
+        .filter(reservas::inicio.gt(ahora))
+        .filter(reservas::estado.ne(EstadoReserva::EnCurso))
+        .distinct_on(reservas::cabana_id)
+        .order((reservas::cabana_id.asc(), reservas::inicio.asc()))
+        .load::<Reserva>(conn)?
+        .into_iter()
+        .map(|r| (r.cabana_id, r))
+        .collect();
+
+    let mut en_mantenimiento: HashMap<i32, Mantenimiento> = mantenimientos::table
+        .filter(mantenimientos::cabana_id.eq_any(ids.clone()))
+        .filter(mantenimientos::inicio.le(ahora))
+        .filter(mantenimientos::fin.gt(ahora))
+        .order(mantenimientos::fin.asc())
+        .load::<Mantenimiento>(conn)?
+        .into_iter()
+        .map(|m| (m.cabana_id, m))
+        .collect();
+
+    Ok(lista
+        .into_iter()
+        .map(|cabana| {
+            let reserva_actual = actuales.remove(&cabana.id);
+            let mantenimiento_actual = en_mantenimiento.remove(&cabana.id);
+            let estado = if mantenimiento_actual.is_some() {
+                EstadoOcupacion::Mantenimiento
+            } else if reserva_actual.is_some() {
+                EstadoOcupacion::Ocupada
+            } else {
+                EstadoOcupacion::Libre
+            };
+            let ocupacion_actual = OcupacionActual {
+                estado,
+                reserva_actual,
+                proxima_reserva: proximas.remove(&cabana.id),
+                mantenimiento_actual,
+            };
+            CabanaConOcupacion { cabana, ocupacion_actual }
+        })
+        .collect())
 }
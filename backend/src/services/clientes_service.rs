@@ -1,6 +1,7 @@
 use diesel::prelude::*;
-use crate::models::{Cliente, NewCliente};
-use crate::schema::clientes;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{Cliente, NewCliente, UpdateCliente};
+use crate::schema::{clientes, reservas};
 
 pub fn listar_clientes(conn: &mut PgConnection) -> QueryResult<Vec<Cliente>> {
     clientes::table
@@ -8,8 +9,72 @@ pub fn listar_clientes(conn: &mut PgConnection) -> QueryResult<Vec<Cliente>> {
         .load::<Cliente>(conn)
 }
 
+pub fn obtener_cliente(conn: &mut PgConnection, cliente_id: i32) -> ApiResult<Cliente> {
+    clientes::table
+        .find(cliente_id)
+        .first::<Cliente>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cliente {} no encontrado", cliente_id)))
+}
+
+/// 🚫 Suma un no presentado al historial del cliente
+pub fn sumar_no_show(conn: &mut PgConnection, cliente_id: i32) -> QueryResult<usize> {
+    diesel::update(clientes::table.find(cliente_id))
+        .set(clientes::no_shows.eq(clientes::no_shows + 1))
+        .execute(conn)
+}
+
 pub fn crear_cliente(conn: &mut PgConnection, nuevo: NewCliente) -> QueryResult<Cliente> {
     diesel::insert_into(clientes::table)
         .values(&nuevo)
         .get_result::<Cliente>(conn)
 }
+
+pub fn reemplazar_cliente(conn: &mut PgConnection, cliente_id: i32, datos: NewCliente) -> ApiResult<Cliente> {
+    diesel::update(clientes::table.find(cliente_id))
+        .set(&datos)
+        .get_result::<Cliente>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cliente {} no encontrado", cliente_id)))
+}
+
+pub fn actualizar_cliente(
+    conn: &mut PgConnection,
+    cliente_id: i32,
+    cambios: UpdateCliente,
+) -> ApiResult<Cliente> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+
+    diesel::update(clientes::table.find(cliente_id))
+        .set(&cambios)
+        .get_result::<Cliente>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Cliente {} no encontrado", cliente_id)))
+}
+
+// =============================
+// ❌ Eliminar cliente
+//  - Se rechaza si tiene reservas para no perder el historial
+// =============================
+pub fn eliminar_cliente(conn: &mut PgConnection, cliente_id: i32) -> ApiResult<usize> {
+    conn.transaction::<usize, ApiError, _>(|conn| {
+        let cliente = obtener_cliente(conn, cliente_id)?;
+
+        let total_reservas = reservas::table
+            .filter(reservas::cliente_id.eq(cliente.id))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        if total_reservas > 0 {
+            return Err(ApiError::conflicto(format!(
+                "El cliente {} tiene reservas registradas y no puede eliminarse",
+                cliente.id
+            ))
+            .con_detalles(serde_json::json!({ "reservas": total_reservas })));
+        }
+
+        Ok(diesel::delete(clientes::table.find(cliente.id)).execute(conn)?)
+    })
+}
@@ -0,0 +1,238 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{Cliente, Comprobante, EstadoReserva, NewComprobante, Pago, Reserva, TipoPago};
+use crate::pdf::{DocumentoPdf, COLUMNAS};
+use crate::schema::{cabanas, clientes, comprobantes, pagos, reservas};
+use crate::services::configuracion_service;
+
+/// Comprobante con los movimientos de caja de su reserva, listo para imprimir
+pub struct DocumentoComprobante {
+    pub comprobante: Comprobante,
+    pub reserva: Reserva,
+    pub pagos: Vec<Pago>,
+}
+
+// =============================
+// 🧾 Emitir el comprobante de una reserva (POST)
+//  - Solo reservas completadas y con monto
+//  - La primera vez se numera y se congelan cliente, descripción e impuestos;
+//    si ya existe se devuelve el mismo, así reintentar no duplica números
+// =============================
+pub fn emitir(conn: &mut PgConnection, reserva_id: i32) -> ApiResult<DocumentoComprobante> {
+    conn.transaction::<DocumentoComprobante, ApiError, _>(|conn| {
+        // Bloquear la reserva evita emitir dos comprobantes si piden a la vez
+        let reserva = reservas::table
+            .find(reserva_id)
+            .for_update()
+            .first::<Reserva>(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+        let comprobante = match existente(conn, reserva.id)? {
+            Some(c) => c,
+            None => numerar(conn, &reserva)?,
+        };
+        documento(conn, comprobante, reserva)
+    })
+}
+
+/// 🖨️ Reimprime el comprobante ya emitido; no numera ni bloquea nada
+pub fn obtener(conn: &mut PgConnection, reserva_id: i32) -> ApiResult<DocumentoComprobante> {
+    let reserva = reservas::table
+        .find(reserva_id)
+        .first::<Reserva>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+    let comprobante = existente(conn, reserva.id)?.ok_or_else(|| {
+        ApiError::no_encontrado(format!("La reserva {} todavía no tiene comprobante emitido", reserva.id))
+    })?;
+    documento(conn, comprobante, reserva)
+}
+
+fn existente(conn: &mut PgConnection, reserva_id: i32) -> QueryResult<Option<Comprobante>> {
+    comprobantes::table
+        .filter(comprobantes::reserva_id.eq(reserva_id))
+        .first::<Comprobante>(conn)
+        .optional()
+}
+
+fn documento(conn: &mut PgConnection, comprobante: Comprobante, reserva: Reserva) -> ApiResult<DocumentoComprobante> {
+    let pagos = pagos::table
+        .filter(pagos::reserva_id.eq(reserva.id))
+        .order((pagos::fecha_pago.asc(), pagos::id.asc()))
+        .load::<Pago>(conn)?;
+
+    Ok(DocumentoComprobante { comprobante, reserva, pagos })
+}
+
+fn numerar(conn: &mut PgConnection, reserva: &Reserva) -> ApiResult<Comprobante> {
+    if reserva.estado != EstadoReserva::Completada {
+        return Err(ApiError::conflicto(format!(
+            "La reserva {} está {}; el comprobante se emite al completarla",
+            reserva.id, reserva.estado
+        )));
+    }
+    let total = reserva.monto_total.clone().ok_or_else(|| {
+        ApiError::validacion(format!("La reserva {} no tiene monto calculado", reserva.id))
+            .con_detalles(serde_json::json!({ "campo": "monto_total" }))
+    })?;
+
+    let cliente = clientes::table.find(reserva.cliente_id).first::<Cliente>(conn)?;
+    let nombre_cabana = cabanas::table
+        .find(reserva.cabana_id)
+        .select(cabanas::nombre)
+        .first::<String>(conn)?;
+
+    // Numeración correlativa sin huecos: nadie más emite hasta el commit
+    diesel::sql_query("LOCK TABLE comprobantes IN EXCLUSIVE MODE").execute(conn)?;
+    let ultimo = comprobantes::table
+        .select(diesel::dsl::max(comprobantes::numero))
+        .first::<Option<i32>>(conn)?;
+
+    // El precio incluye IVA: base = total / (1 + tasa). La tasa vigente sale
+    // de la configuración y queda congelada en el comprobante
+    let tasa = configuracion_service::obtener(conn)?.tasa_iva;
+    let base_imponible = (&total * BigDecimal::from(100) / (BigDecimal::from(100) + &tasa))
+        .with_scale_round(2, RoundingMode::HalfUp);
+    let impuesto = &total - &base_imponible;
+
+    let horas = BigDecimal::from(((reserva.fin - reserva.inicio).num_seconds() + 59) / 60) / BigDecimal::from(60);
+    let descripcion = format!(
+        "Alquiler de {} el {} de {} a {} ({} h, {} personas)",
+        nombre_cabana,
+        reserva.fecha_reserva.format("%d/%m/%Y"),
+        reserva.hora_inicio.format("%H:%M"),
+        reserva.hora_fin.format("%H:%M"),
+        horas.with_scale_round(2, RoundingMode::HalfUp),
+        reserva.num_personas
+    );
+
+    Ok(diesel::insert_into(comprobantes::table)
+        .values(&NewComprobante {
+            numero: ultimo.unwrap_or(0) + 1,
+            reserva_id: reserva.id,
+            nombre_cliente: cliente.nombre,
+            nit: cliente.nit,
+            razon_social: cliente.razon_social,
+            descripcion,
+            base_imponible,
+            tasa_impuesto: tasa.with_scale(2),
+            impuesto,
+            total,
+        })
+        .get_result::<Comprobante>(conn)?)
+}
+
+// =============================
+// 🖨️ Formatos de salida
+// =============================
+
+/// Líneas comunes a ambos formatos: (etiqueta, valor)
+fn encabezado(doc: &DocumentoComprobante) -> Vec<(&'static str, String)> {
+    let c = &doc.comprobante;
+    vec![
+        ("Comprobante N°", format!("{:08}", c.numero)),
+        ("Fecha de emisión", c.fecha_emision.format("%d/%m/%Y %H:%M").to_string()),
+        ("Reserva", doc.reserva.id.to_string()),
+        ("Cliente", c.nombre_cliente.clone()),
+        ("Razón social", c.razon_social.clone().unwrap_or_else(|| "S/N".to_string())),
+        ("NIT", c.nit.clone().unwrap_or_else(|| "0".to_string())),
+    ]
+}
+
+fn totales(doc: &DocumentoComprobante) -> Vec<(String, String)> {
+    let c = &doc.comprobante;
+    vec![
+        ("Base imponible".to_string(), c.base_imponible.to_string()),
+        (format!("IVA {}%", c.tasa_impuesto.with_scale(0)), c.impuesto.to_string()),
+        ("TOTAL".to_string(), c.total.to_string()),
+    ]
+}
+
+fn fila_pago(p: &Pago) -> (String, String) {
+    let signo = if p.tipo == TipoPago::Reembolso { "-" } else { "" };
+    let referencia = p.referencia.as_deref().map(|r| format!(" ({})", r)).unwrap_or_default();
+    (
+        format!("{} {} {}{}", p.fecha_pago.format("%d/%m/%Y %H:%M"), p.tipo, p.metodo, referencia),
+        format!("{}{}", signo, p.monto),
+    )
+}
+
+fn saldo(doc: &DocumentoComprobante) -> String {
+    doc.reserva.saldo.as_ref().map(|s| s.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn renderizar_html(doc: &DocumentoComprobante) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"es\">\n<head>\n<meta charset=\"utf-8\">\n<title>Comprobante</title>\n\
+         <style>body{font-family:sans-serif;max-width:640px;margin:2em auto}\
+         table{width:100%;border-collapse:collapse}td{padding:4px}\
+         td.monto{text-align:right}tr.total td{font-weight:bold;border-top:1px solid #000}</style>\n\
+         </head>\n<body>\n<h1>Dubai Resto Bar</h1>\n<table>\n",
+    );
+    for (etiqueta, valor) in encabezado(doc) {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", etiqueta, escapar(&valor)));
+    }
+    html.push_str("</table>\n<h2>Detalle</h2>\n<table>\n");
+    html.push_str(&format!(
+        "<tr><td>{}</td><td class=\"monto\">{}</td></tr>\n",
+        escapar(&doc.comprobante.descripcion),
+        doc.comprobante.total
+    ));
+    for (i, (etiqueta, valor)) in totales(doc).into_iter().enumerate() {
+        let clase = if i == 2 { " class=\"total\"" } else { "" };
+        html.push_str(&format!("<tr{}><td>{}</td><td class=\"monto\">{}</td></tr>\n", clase, etiqueta, valor));
+    }
+    html.push_str("</table>\n<h2>Pagos</h2>\n<table>\n");
+    for p in &doc.pagos {
+        let (detalle, monto) = fila_pago(p);
+        html.push_str(&format!("<tr><td>{}</td><td class=\"monto\">{}</td></tr>\n", escapar(&detalle), monto));
+    }
+    html.push_str(&format!(
+        "<tr class=\"total\"><td>Saldo pendiente</td><td class=\"monto\">{}</td></tr>\n</table>\n</body>\n</html>\n",
+        saldo(doc)
+    ));
+    html
+}
+
+pub fn renderizar_pdf(doc: &DocumentoComprobante) -> Vec<u8> {
+    let mut pdf = DocumentoPdf::new();
+    pdf.titulo("Dubai Resto Bar").linea("");
+
+    for (etiqueta, valor) in encabezado(doc) {
+        pdf.linea(&format!("{:<18}{}", etiqueta, valor));
+    }
+
+    pdf.linea("").negrita("Detalle").separador();
+    pdf.linea(&columnas(&doc.comprobante.descripcion, &doc.comprobante.total.to_string()));
+    pdf.separador();
+    for (etiqueta, valor) in totales(doc) {
+        pdf.linea(&columnas(&etiqueta, &valor));
+    }
+
+    pdf.linea("").negrita("Pagos").separador();
+    for p in &doc.pagos {
+        let (detalle, monto) = fila_pago(p);
+        pdf.linea(&columnas(&detalle, &monto));
+    }
+    pdf.separador().negrita(&columnas("Saldo pendiente", &saldo(doc)));
+
+    pdf.renderizar()
+}
+
+/// Texto a la izquierda e importe alineado a la derecha en una línea de `COLUMNAS`
+fn columnas(texto: &str, monto: &str) -> String {
+    let ancho_texto = COLUMNAS.saturating_sub(monto.chars().count() + 1);
+    let texto: String = texto.chars().take(ancho_texto).collect();
+    format!("{:<ancho$} {}", texto, monto, ancho = ancho_texto)
+}
+
+fn escapar(texto: &str) -> String {
+    texto
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,43 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{Configuracion, UpdateConfiguracion};
+use crate::schema::configuracion;
+
+/// ⚙️ Parámetros globales (la migración crea la única fila)
+pub fn obtener(conn: &mut PgConnection) -> QueryResult<Configuracion> {
+    configuracion::table
+        .select(Configuracion::as_select())
+        .first::<Configuracion>(conn)
+}
+
+pub fn actualizar(conn: &mut PgConnection, cambios: UpdateConfiguracion) -> ApiResult<Configuracion> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+    if matches!(cambios.minutos_limpieza, Some(m) if m < 0) {
+        return Err(ApiError::validacion("Los minutos de limpieza no pueden ser negativos")
+            .con_detalles(serde_json::json!({ "campo": "minutos_limpieza" })));
+    }
+    if matches!(cambios.minutos_oferta_espera, Some(m) if m < 1) {
+        return Err(ApiError::validacion("El plazo de las ofertas debe ser de al menos 1 minuto")
+            .con_detalles(serde_json::json!({ "campo": "minutos_oferta_espera" })));
+    }
+    if matches!(cambios.minutos_bloqueo, Some(m) if m < 1) {
+        return Err(ApiError::validacion("Los bloqueos deben durar al menos 1 minuto")
+            .con_detalles(serde_json::json!({ "campo": "minutos_bloqueo" })));
+    }
+    if matches!(cambios.minutos_tolerancia_no_show, Some(m) if m < 0) {
+        return Err(ApiError::validacion("La tolerancia para no presentados no puede ser negativa")
+            .con_detalles(serde_json::json!({ "campo": "minutos_tolerancia_no_show" })));
+    }
+    if matches!(&cambios.tasa_iva, Some(t) if *t < BigDecimal::from(0) || *t >= BigDecimal::from(100)) {
+        return Err(ApiError::validacion("La tasa de IVA debe estar entre 0 y 100")
+            .con_detalles(serde_json::json!({ "campo": "tasa_iva" })));
+    }
+
+    Ok(diesel::update(configuracion::table)
+        .set(&cambios)
+        .returning(Configuracion::as_returning())
+        .get_result::<Configuracion>(conn)?)
+}
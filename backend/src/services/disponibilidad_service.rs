@@ -0,0 +1,257 @@
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    Cabana, DisponibilidadCabana, EstadoCabana, EstadoReserva, FiltroDisponibilidad, Intervalo,
+};
+use crate::schema::{bloqueos, cabanas, reservas};
+use crate::services::validaciones_service::{parsear_fecha, parsear_hora};
+
+// =============================
+// 🟢 Disponibilidad de una cabaña en un día completo
+// =============================
+pub fn disponibilidad_cabana(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    fecha: Option<&str>,
+) -> ApiResult<DisponibilidadCabana> {
+    use crate::services::cabanas_service;
+
+    let cabana = cabanas_service::obtener_cabana_activa(conn, cabana_id)?;
+    let ventana = ventana_consulta(fecha, None, None)?;
+
+    let mut resultado = calcular(conn, vec![cabana], ventana)?;
+    Ok(resultado.remove(0))
+}
+
+// =============================
+// 🔎 Búsqueda de cabañas libres
+//  - Solo cabañas en servicio y con capacidad suficiente
+//  - Solo se devuelven las que tienen algún hueco en la ventana (las ventanas
+//    de mantenimiento cuentan como ocupadas)
+// =============================
+pub fn buscar_disponibilidad(
+    conn: &mut PgConnection,
+    filtro: FiltroDisponibilidad,
+) -> ApiResult<Vec<DisponibilidadCabana>> {
+    let ventana = ventana_consulta(
+        filtro.fecha.as_deref(),
+        filtro.desde.as_deref(),
+        filtro.hasta.as_deref(),
+    )?;
+
+    let mut query = cabanas::table
+        .filter(cabanas::archivada.eq(false))
+        .filter(cabanas::estado.eq(EstadoCabana::Activa))
+        .order(cabanas::id.asc())
+        .into_boxed();
+
+    if let Some(personas) = filtro.capacidad {
+        query = query
+            .filter(cabanas::capacidad.ge(personas))
+            .filter(cabanas::ocupacion_minima.le(personas));
+    }
+
+    let candidatas = query.load::<Cabana>(conn)?;
+
+    Ok(calcular(conn, candidatas, ventana)?
+        .into_iter()
+        .filter(|d| !d.libres.is_empty())
+        .collect())
+}
+
+/// Arma la ventana `[inicio, fin)` a partir de los parámetros de consulta.
+/// Sin horas, cubre el día entero; si `hasta <= desde`, termina al día siguiente.
+fn ventana_consulta(
+    fecha: Option<&str>,
+    desde: Option<&str>,
+    hasta: Option<&str>,
+) -> ApiResult<Intervalo> {
+    let fecha: NaiveDate = match fecha {
+        Some(v) => parsear_fecha("fecha", v)?,
+        None => Local::now().date_naive(),
+    };
+    let desde = desde.map(|v| parsear_hora("desde", v)).transpose()?.unwrap_or(NaiveTime::MIN);
+    let inicio = fecha.and_time(desde);
+
+    let fin = match hasta.map(|v| parsear_hora("hasta", v)).transpose()? {
+        Some(h) if h > desde => fecha.and_time(h),
+        Some(h) => (fecha + Duration::days(1)).and_time(h),
+        None => (fecha + Duration::days(1)).and_time(NaiveTime::MIN),
+    };
+
+    if fin <= inicio {
+        return Err(ApiError::validacion("La ventana consultada está vacía"));
+    }
+
+    Ok(Intervalo { inicio, fin })
+}
+
+/// Calcula los huecos libres de cada cabaña restando sus reservas activas y
+/// sus mantenimientos, y dejando solo las horas en que el local está abierto
+fn calcular(
+    conn: &mut PgConnection,
+    candidatas: Vec<Cabana>,
+    ventana: Intervalo,
+) -> QueryResult<Vec<DisponibilidadCabana>> {
+    use crate::services::{configuracion_service, horarios_service, mantenimientos_service};
+
+    let por_defecto = configuracion_service::obtener(conn)?.minutos_limpieza;
+    let margenes: HashMap<i32, Duration> = candidatas
+        .iter()
+        .map(|c| (c.id, Duration::minutes(c.minutos_limpieza.unwrap_or(por_defecto) as i64)))
+        .collect();
+    let mut ocupados = ocupaciones(conn, &margenes, ventana)?;
+    let ids: Vec<i32> = candidatas.iter().map(|c| c.id).collect();
+    for (cabana_id, ventanas) in mantenimientos_service::ventanas_por_cabana(conn, &ids, ventana)? {
+        ocupados.entry(cabana_id).or_default().extend(ventanas);
+    }
+    let abiertos = horarios_service::horas_abiertas(conn, ventana.inicio, ventana.fin)?;
+
+    Ok(candidatas
+        .into_iter()
+        .map(|c| {
+            let libres = restar_ocupados(ventana, ocupados.remove(&c.id).unwrap_or_default());
+            let libres = intersectar(&libres, &abiertos);
+            DisponibilidadCabana {
+                cabana_id: c.id,
+                nombre: c.nombre,
+                capacidad: c.capacidad,
+                ocupacion_minima: c.ocupacion_minima,
+                estado: c.estado,
+                libres,
+            }
+        })
+        .collect())
+}
+
+/// Intervalos ocupados por cabaña que tocan la ventana (reservas activas y
+/// bloqueos vigentes), ampliados con el margen de limpieza de cada cabaña
+/// antes y después de cada uno
+fn ocupaciones(
+    conn: &mut PgConnection,
+    margenes: &HashMap<i32, Duration>,
+    ventana: Intervalo,
+) -> QueryResult<HashMap<i32, Vec<Intervalo>>> {
+    let cabana_ids: Vec<i32> = margenes.keys().copied().collect();
+    let mayor = margenes.values().copied().max().unwrap_or_else(Duration::zero);
+
+    let filas = reservas::table
+        .filter(reservas::cabana_id.eq_any(&cabana_ids))
+        .filter(reservas::estado.ne_all(EstadoReserva::LIBERAN_CABANA))
+        .filter(reservas::inicio.lt(ventana.fin + mayor))
+        .filter(reservas::fin.gt(ventana.inicio - mayor))
+        .select((reservas::cabana_id, reservas::inicio, reservas::fin))
+        .load::<(i32, NaiveDateTime, NaiveDateTime)>(conn)?;
+    let bloqueados = bloqueos::table
+        .filter(bloqueos::cabana_id.eq_any(&cabana_ids))
+        .filter(bloqueos::vence.gt(Local::now().naive_local()))
+        .filter(bloqueos::inicio.lt(ventana.fin + mayor))
+        .filter(bloqueos::fin.gt(ventana.inicio - mayor))
+        .select((bloqueos::cabana_id, bloqueos::inicio, bloqueos::fin))
+        .load::<(i32, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    let mut por_cabana: HashMap<i32, Vec<Intervalo>> = HashMap::new();
+    for (cabana_id, inicio, fin) in filas.into_iter().chain(bloqueados) {
+        let margen = margenes.get(&cabana_id).copied().unwrap_or_else(Duration::zero);
+        por_cabana
+            .entry(cabana_id)
+            .or_default()
+            .push(Intervalo { inicio: inicio - margen, fin: fin + margen });
+    }
+    Ok(por_cabana)
+}
+
+/// Resta los ocupados de la ventana usando la misma regla de solape que
+/// `validar_conflictos`: dos intervalos chocan si `a.inicio < b.fin && a.fin > b.inicio`
+fn restar_ocupados(ventana: Intervalo, mut ocupados: Vec<Intervalo>) -> Vec<Intervalo> {
+    ocupados.sort_by_key(|o| o.inicio);
+
+    let mut libres = Vec::new();
+    let mut cursor: NaiveDateTime = ventana.inicio;
+
+    for o in ocupados {
+        if o.inicio >= ventana.fin || o.fin <= ventana.inicio {
+            continue;
+        }
+        if o.inicio > cursor {
+            libres.push(Intervalo { inicio: cursor, fin: o.inicio });
+        }
+        cursor = cursor.max(o.fin);
+    }
+
+    if cursor < ventana.fin {
+        libres.push(Intervalo { inicio: cursor, fin: ventana.fin });
+    }
+    libres
+}
+
+/// Parte común de dos listas de intervalos ordenados y sin solapes internos
+fn intersectar(a: &[Intervalo], b: &[Intervalo]) -> Vec<Intervalo> {
+    let mut resultado = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        let inicio = a[i].inicio.max(b[j].inicio);
+        let fin = a[i].fin.min(b[j].fin);
+        if inicio < fin {
+            resultado.push(Intervalo { inicio, fin });
+        }
+        if a[i].fin < b[j].fin {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Intervalo del 2026-10-23 entre dos horas (en horas enteras)
+    fn iv(desde: u32, hasta: u32) -> Intervalo {
+        let dia = NaiveDate::from_ymd_opt(2026, 10, 23).unwrap();
+        Intervalo {
+            inicio: dia.and_hms_opt(desde, 0, 0).unwrap(),
+            fin: dia.and_hms_opt(hasta, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sin_ocupados_queda_la_ventana_entera() {
+        assert_eq!(restar_ocupados(iv(10, 20), vec![]), vec![iv(10, 20)]);
+    }
+
+    #[test]
+    fn resta_ocupados_desordenados_y_solapados() {
+        let ocupados = vec![iv(15, 17), iv(12, 13), iv(16, 18), iv(12, 14)];
+        assert_eq!(restar_ocupados(iv(10, 20), ocupados), vec![iv(10, 12), iv(14, 15), iv(18, 20)]);
+    }
+
+    #[test]
+    fn ignora_los_ocupados_fuera_de_la_ventana_y_recorta_los_del_borde() {
+        let ocupados = vec![iv(6, 10), iv(20, 22), iv(8, 11), iv(19, 23)];
+        assert_eq!(restar_ocupados(iv(10, 20), ocupados), vec![iv(11, 19)]);
+    }
+
+    #[test]
+    fn ocupados_contiguos_no_dejan_huecos_vacios() {
+        assert_eq!(restar_ocupados(iv(10, 20), vec![iv(10, 15), iv(15, 20)]), vec![]);
+    }
+
+    #[test]
+    fn intersecta_listas_de_intervalos() {
+        let a = [iv(8, 12), iv(14, 20)];
+        let b = [iv(10, 15), iv(16, 17), iv(19, 23)];
+        assert_eq!(intersectar(&a, &b), vec![iv(10, 12), iv(14, 15), iv(16, 17), iv(19, 20)]);
+    }
+
+    #[test]
+    fn intervalos_que_solo_se_tocan_no_se_intersectan() {
+        assert_eq!(intersectar(&[iv(8, 10)], &[iv(10, 12)]), vec![]);
+        assert_eq!(intersectar(&[], &[iv(10, 12)]), vec![]);
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{ExcepcionHorario, HorarioAtencion, HorarioDia, Intervalo};
+use crate::schema::{excepciones_horario, horarios_atencion};
+
+// =============================
+// 🕘 Horario semanal
+// =============================
+pub fn listar_horario(conn: &mut PgConnection) -> QueryResult<Vec<HorarioAtencion>> {
+    horarios_atencion::table
+        .order(horarios_atencion::dia_semana.asc())
+        .load::<HorarioAtencion>(conn)
+}
+
+pub fn actualizar_dia(conn: &mut PgConnection, dia_semana: i32, horario: HorarioDia) -> ApiResult<HorarioAtencion> {
+    if !(1..=7).contains(&dia_semana) {
+        return Err(ApiError::validacion("El día debe ir de 1 (lunes) a 7 (domingo)")
+            .con_detalles(serde_json::json!({ "campo": "dia_semana" })));
+    }
+    validar_horas(horario.cerrado, horario.abre, horario.cierra)?;
+
+    let horario = HorarioDia {
+        abre: horario.abre.filter(|_| !horario.cerrado),
+        cierra: horario.cierra.filter(|_| !horario.cerrado),
+        cerrado: horario.cerrado,
+    };
+
+    Ok(diesel::insert_into(horarios_atencion::table)
+        .values((
+            horarios_atencion::dia_semana.eq(dia_semana),
+            horarios_atencion::abre.eq(horario.abre),
+            horarios_atencion::cierra.eq(horario.cierra),
+            horarios_atencion::cerrado.eq(horario.cerrado),
+        ))
+        .on_conflict(horarios_atencion::dia_semana)
+        .do_update()
+        .set(&horario)
+        .get_result::<HorarioAtencion>(conn)?)
+}
+
+// =============================
+// 📆 Excepciones por fecha
+// =============================
+pub fn listar_excepciones(conn: &mut PgConnection, desde: Option<NaiveDate>) -> QueryResult<Vec<ExcepcionHorario>> {
+    let mut query = excepciones_horario::table
+        .order(excepciones_horario::fecha.asc())
+        .into_boxed();
+
+    if let Some(fecha) = desde {
+        query = query.filter(excepciones_horario::fecha.ge(fecha));
+    }
+
+    query.load::<ExcepcionHorario>(conn)
+}
+
+/// Crea la excepción o reemplaza la que ya hubiera ese día
+pub fn guardar_excepcion(conn: &mut PgConnection, mut excepcion: ExcepcionHorario) -> ApiResult<ExcepcionHorario> {
+    validar_horas(excepcion.cerrado, excepcion.abre, excepcion.cierra)?;
+    if excepcion.cerrado {
+        excepcion.abre = None;
+        excepcion.cierra = None;
+    }
+
+    Ok(diesel::insert_into(excepciones_horario::table)
+        .values(&excepcion)
+        .on_conflict(excepciones_horario::fecha)
+        .do_update()
+        .set((
+            excepciones_horario::cerrado.eq(excepcion.cerrado),
+            excepciones_horario::abre.eq(excepcion.abre),
+            excepciones_horario::cierra.eq(excepcion.cierra),
+            excepciones_horario::motivo.eq(&excepcion.motivo),
+        ))
+        .get_result::<ExcepcionHorario>(conn)?)
+}
+
+pub fn eliminar_excepcion(conn: &mut PgConnection, fecha: NaiveDate) -> ApiResult<()> {
+    let borradas = diesel::delete(excepciones_horario::table.find(fecha)).execute(conn)?;
+    if borradas == 0 {
+        return Err(ApiError::no_encontrado(format!("No hay excepción de horario el {}", fecha)));
+    }
+    Ok(())
+}
+
+fn validar_horas(cerrado: bool, abre: Option<NaiveTime>, cierra: Option<NaiveTime>) -> ApiResult<()> {
+    if !cerrado && (abre.is_none() || cierra.is_none()) {
+        return Err(ApiError::validacion("Indica abre y cierra, o marca el día como cerrado")
+            .con_detalles(serde_json::json!({ "campo": if abre.is_none() { "abre" } else { "cierra" } })));
+    }
+    Ok(())
+}
+
+// =============================
+// ✅ Control de reservas contra el horario
+// =============================
+
+/// Valida que `[inicio, fin)` caiga entero en horas abiertas; si no, 422 con
+/// los tramos abiertos de esas fechas para que la UI pueda sugerir otro horario
+pub fn validar_en_horario(conn: &mut PgConnection, inicio: NaiveDateTime, fin: NaiveDateTime) -> ApiResult<()> {
+    let abiertos = horas_abiertas(conn, inicio, fin)?;
+
+    if abiertos.iter().any(|a| a.inicio <= inicio && a.fin >= fin) {
+        return Ok(());
+    }
+
+    Err(ApiError::validacion("La reserva queda fuera del horario de atención")
+        .con_detalles(serde_json::json!({ "campo": "hora_inicio", "abierto": abiertos })))
+}
+
+/// Tramos abiertos que tocan `[desde, hasta)`, ya fusionados: dos jornadas
+/// seguidas de 24 h (o una que cierra cuando abre la siguiente) son un solo tramo
+pub fn horas_abiertas(
+    conn: &mut PgConnection,
+    desde: NaiveDateTime,
+    hasta: NaiveDateTime,
+) -> QueryResult<Vec<Intervalo>> {
+    // La jornada del día anterior puede extenderse pasada la medianoche
+    let primer_dia = desde.date() - Duration::days(1);
+    let ultimo_dia = hasta.date();
+
+    let semanal: HashMap<i32, HorarioAtencion> = listar_horario(conn)?
+        .into_iter()
+        .map(|h| (h.dia_semana, h))
+        .collect();
+    let excepciones: HashMap<NaiveDate, ExcepcionHorario> = excepciones_horario::table
+        .filter(excepciones_horario::fecha.between(primer_dia, ultimo_dia))
+        .load::<ExcepcionHorario>(conn)?
+        .into_iter()
+        .map(|e| (e.fecha, e))
+        .collect();
+
+    let mut abiertos: Vec<Intervalo> = Vec::new();
+    let mut dia = primer_dia;
+    while dia <= ultimo_dia {
+        let horas = match excepciones.get(&dia) {
+            Some(e) => (!e.cerrado).then_some((e.abre, e.cierra)),
+            None => semanal
+                .get(&(dia.weekday().number_from_monday() as i32))
+                .and_then(|h| (!h.cerrado).then_some((h.abre, h.cierra))),
+        };
+
+        if let Some((Some(abre), Some(cierra))) = horas {
+            let inicio = dia.and_time(abre);
+            let fin = if cierra > abre { dia.and_time(cierra) } else { (dia + Duration::days(1)).and_time(cierra) };
+
+            match abiertos.last_mut() {
+                Some(ultimo) if inicio <= ultimo.fin => ultimo.fin = ultimo.fin.max(fin),
+                _ => abiertos.push(Intervalo { inicio, fin }),
+            }
+        }
+        dia += Duration::days(1);
+    }
+
+    Ok(abiertos.into_iter().filter(|a| a.inicio < hasta && a.fin > desde).collect())
+}
@@ -0,0 +1,360 @@
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    Cabana, EntradaEspera, EstadoCabana, EstadoEspera, EstadoOferta, EstadoReserva, FiltroListaEspera,
+    NewBloqueo, NewEntradaEspera, NewOfertaEspera, NewReserva, OfertaEspera, Reserva,
+};
+use crate::schema::{lista_espera, ofertas_espera};
+
+// =============================
+// ⏳ Anotarse en la lista de espera
+//  - Se guarda el horario deseado aunque hoy esté ocupado: eso es justamente
+//    lo que espera el cliente
+// =============================
+pub fn listar(conn: &mut PgConnection, filtro: FiltroListaEspera) -> ApiResult<Vec<EntradaEspera>> {
+    use crate::services::validaciones_service::parsear_fecha;
+
+    let mut query = lista_espera::table
+        .order((lista_espera::fecha_creacion.asc(), lista_espera::id.asc()))
+        .into_boxed();
+
+    // Sin filtro de estado, solo las que siguen abiertas
+    query = match filtro.estado.as_deref() {
+        Some(v) => query.filter(lista_espera::estado.eq(v.parse::<EstadoEspera>()?)),
+        None => query.filter(lista_espera::estado.eq_any([EstadoEspera::Esperando, EstadoEspera::Ofertada])),
+    };
+    if let Some(v) = filtro.fecha.as_deref() {
+        query = query.filter(lista_espera::fecha.eq(parsear_fecha("fecha", v)?));
+    }
+
+    Ok(query.load::<EntradaEspera>(conn)?)
+}
+
+pub fn crear(conn: &mut PgConnection, nueva: NewEntradaEspera) -> ApiResult<EntradaEspera> {
+    use crate::services::{cabanas_service, clientes_service, validaciones_service};
+
+    clientes_service::obtener_cliente(conn, nueva.cliente_id)?;
+
+    validaciones_service::validar_horas(nueva.hora_inicio, nueva.hora_fin)?;
+    if nueva.num_personas < 1 {
+        return Err(ApiError::validacion("La espera debe ser para al menos 1 persona")
+            .con_detalles(serde_json::json!({ "campo": "num_personas" })));
+    }
+    let (inicio, _) = validaciones_service::rango_reserva(nueva.fecha, nueva.hora_inicio, nueva.hora_fin);
+    if inicio <= Local::now().naive_local() {
+        return Err(ApiError::validacion("El horario deseado ya pasó")
+            .con_detalles(serde_json::json!({ "campo": "fecha" })));
+    }
+
+    if let Some(ids) = &nueva.cabana_ids {
+        if ids.is_empty() {
+            return Err(ApiError::validacion("Indica al menos una cabaña u omite el campo para aceptar cualquiera")
+                .con_detalles(serde_json::json!({ "campo": "cabana_ids" })));
+        }
+        // Al menos una de las cabañas elegidas tiene que poder alojar al grupo
+        let mut alguna = false;
+        for id in ids {
+            let cabana = cabanas_service::obtener_cabana(conn, *id)?;
+            alguna |= validaciones_service::validar_personas(&cabana, nueva.num_personas).is_ok();
+        }
+        if !alguna {
+            return Err(ApiError::validacion("Ninguna de las cabañas elegidas admite ese número de personas")
+                .con_detalles(serde_json::json!({ "campo": "num_personas" })));
+        }
+    }
+
+    Ok(diesel::insert_into(lista_espera::table)
+        .values(&nueva)
+        .get_result::<EntradaEspera>(conn)?)
+}
+
+/// Da de baja la entrada; si tenía una oferta abierta, la cabaña pasa al siguiente
+pub fn cancelar(conn: &mut PgConnection, espera_id: i32) -> ApiResult<(EntradaEspera, Vec<OfertaEspera>)> {
+    conn.transaction::<_, ApiError, _>(|conn| {
+        let entrada = buscar(conn, espera_id)?;
+        if !matches!(entrada.estado, EstadoEspera::Esperando | EstadoEspera::Ofertada) {
+            return Err(ApiError::conflicto(format!(
+                "La entrada {} ya está {}",
+                entrada.id,
+                entrada.estado.as_str()
+            )));
+        }
+
+        let entrada = diesel::update(lista_espera::table.find(espera_id))
+            .set(lista_espera::estado.eq(EstadoEspera::Cancelada))
+            .get_result::<EntradaEspera>(conn)?;
+
+        let pendiente = ofertas_espera::table
+            .filter(ofertas_espera::espera_id.eq(espera_id))
+            .filter(ofertas_espera::estado.eq(EstadoOferta::Pendiente))
+            .for_update()
+            .first::<OfertaEspera>(conn)
+            .optional()?;
+        let ofertas = match pendiente {
+            Some(oferta) => cerrar_y_reofrecer(conn, oferta, EstadoOferta::Rechazada, &entrada)?,
+            None => Vec::new(),
+        };
+
+        Ok((entrada, ofertas))
+    })
+}
+
+// =============================
+// 📨 Ofertas
+// =============================
+pub fn listar_ofertas(conn: &mut PgConnection, filtro: FiltroListaEspera) -> ApiResult<Vec<OfertaEspera>> {
+    use crate::services::validaciones_service::parsear_fecha;
+
+    let estado = match filtro.estado.as_deref() {
+        Some(v) => v.parse::<EstadoOferta>()?,
+        None => EstadoOferta::Pendiente,
+    };
+    let mut query = ofertas_espera::table
+        .inner_join(lista_espera::table)
+        .filter(ofertas_espera::estado.eq(estado))
+        .order((ofertas_espera::vence.asc(), ofertas_espera::id.asc()))
+        .select(ofertas_espera::all_columns)
+        .into_boxed();
+
+    if let Some(v) = filtro.fecha.as_deref() {
+        query = query.filter(lista_espera::fecha.eq(parsear_fecha("fecha", v)?));
+    }
+
+    Ok(query.load::<OfertaEspera>(conn)?)
+}
+
+/// ✅ Convierte la oferta en reserva con las validaciones de `crear_reserva`,
+/// salvo anticipación y granularidad: se controlaron al ofrecer (`admite`) y el
+/// plazo para aceptar no debe hacer caer una oferta vigente
+pub fn aceptar_oferta(conn: &mut PgConnection, oferta_id: i32) -> ApiResult<Reserva> {
+    use crate::services::{bloqueos_service, reservas_service};
+
+    conn.transaction::<Reserva, ApiError, _>(|conn| {
+        let oferta = oferta_pendiente(conn, oferta_id)?;
+        let entrada = buscar(conn, oferta.espera_id)?;
+
+        // El horario retenido para la oferta chocaría con la propia reserva;
+        // si `crear_reserva` falla, la transacción lo devuelve
+        bloqueos_service::soltar_oferta(conn, oferta.id)?;
+
+        let reserva = reservas_service::crear_reserva(
+            conn,
+            NewReserva {
+                cliente_id: entrada.cliente_id,
+                cabana_id: oferta.cabana_id,
+                fecha_reserva: entrada.fecha,
+                hora_inicio: entrada.hora_inicio,
+                hora_fin: entrada.hora_fin,
+                estado: EstadoReserva::default(),
+                observaciones: entrada.observaciones.clone(),
+                num_personas: entrada.num_personas,
+                codigo_promocion: None,
+                ignorar_limpieza: false,
+                serie_id: None,
+            },
+            true,
+        )?;
+
+        diesel::update(ofertas_espera::table.find(oferta.id))
+            .set((ofertas_espera::estado.eq(EstadoOferta::Aceptada), ofertas_espera::reserva_id.eq(reserva.id)))
+            .execute(conn)?;
+        diesel::update(lista_espera::table.find(entrada.id))
+            .set(lista_espera::estado.eq(EstadoEspera::Atendida))
+            .execute(conn)?;
+
+        Ok(reserva)
+    })
+}
+
+/// ❌ El cliente no la quiere: vuelve a esperar y la cabaña pasa al siguiente
+pub fn rechazar_oferta(conn: &mut PgConnection, oferta_id: i32) -> ApiResult<Vec<OfertaEspera>> {
+    conn.transaction::<_, ApiError, _>(|conn| {
+        let oferta = oferta_pendiente(conn, oferta_id)?;
+        let entrada = diesel::update(lista_espera::table.find(oferta.espera_id))
+            .set(lista_espera::estado.eq(EstadoEspera::Esperando))
+            .get_result::<EntradaEspera>(conn)?;
+        cerrar_y_reofrecer(conn, oferta, EstadoOferta::Rechazada, &entrada)
+    })
+}
+
+// =============================
+// 🕒 Vencimiento de ofertas (lo llama el actualizador en segundo plano)
+//  - Devuelve cuántas vencieron y las ofertas nuevas que se generaron con
+//    las cabañas liberadas
+// =============================
+pub fn vencer_ofertas(conn: &mut PgConnection) -> ApiResult<(usize, Vec<OfertaEspera>)> {
+    conn.transaction::<_, ApiError, _>(|conn| {
+        let vencidas = ofertas_espera::table
+            .filter(ofertas_espera::estado.eq(EstadoOferta::Pendiente))
+            .filter(ofertas_espera::vence.le(Local::now().naive_local()))
+            .order(ofertas_espera::id.asc())
+            .for_update()
+            .load::<OfertaEspera>(conn)?;
+
+        let cantidad = vencidas.len();
+        let mut nuevas = Vec::new();
+        for oferta in vencidas {
+            let entrada = diesel::update(lista_espera::table.find(oferta.espera_id))
+                .set(lista_espera::estado.eq(EstadoEspera::Esperando))
+                .get_result::<EntradaEspera>(conn)?;
+            nuevas.extend(cerrar_y_reofrecer(conn, oferta, EstadoOferta::Vencida, &entrada)?);
+        }
+        Ok((cantidad, nuevas))
+    })
+}
+
+// =============================
+// 🎯 Ofrecer un hueco liberado
+//  - Se recorren las entradas que esperan ese horario por orden de llegada
+//  - Solo se ofrece lo que hoy se podría reservar de verdad (capacidad,
+//    reglas, horario, mantenimiento y choques, sin forzar la limpieza)
+//  - Una entrada que ya rechazó o dejó vencer esta cabaña no la vuelve a recibir
+//  - Entre entradas que se pisan gana la primera: su oferta retiene el horario
+//    con un bloqueo hasta que se acepte, se rechace o venza, así que las
+//    siguientes ya no pasan `admite`
+// =============================
+pub fn ofrecer_hueco(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+) -> ApiResult<Vec<OfertaEspera>> {
+    use crate::services::{bloqueos_service, cabanas_service, configuracion_service};
+
+    let ahora = Local::now().naive_local();
+    // La oferta retiene el horario con un bloqueo: mismo lock que cualquier alta
+    let cabana = cabanas_service::bloquear_cabana(conn, cabana_id)?;
+    // Archivada o fuera de servicio no se ofrece: no se podría reservar
+    if cabana.archivada || cabana.estado != EstadoCabana::Activa || fin <= ahora {
+        return Ok(Vec::new());
+    }
+
+    let candidatas = lista_espera::table
+        .filter(lista_espera::estado.eq(EstadoEspera::Esperando))
+        .filter(lista_espera::inicio.lt(fin))
+        .filter(lista_espera::fin.gt(inicio))
+        .filter(lista_espera::inicio.gt(ahora))
+        .order((lista_espera::fecha_creacion.asc(), lista_espera::id.asc()))
+        .for_update()
+        .load::<EntradaEspera>(conn)?;
+
+    let plazo = Duration::minutes(configuracion_service::obtener(conn)?.minutos_oferta_espera as i64);
+    let mut ofertas = Vec::new();
+
+    for entrada in candidatas {
+        if entrada.cabana_ids.as_ref().is_some_and(|ids| !ids.contains(&cabana.id)) {
+            continue;
+        }
+        if ya_la_descarto(conn, entrada.id, cabana.id)? || !admite(conn, &cabana, &entrada)? {
+            continue;
+        }
+
+        let oferta = diesel::insert_into(ofertas_espera::table)
+            .values(&NewOfertaEspera { espera_id: entrada.id, cabana_id: cabana.id, vence: ahora + plazo })
+            .get_result::<OfertaEspera>(conn)?;
+        bloqueos_service::retener(
+            conn,
+            &NewBloqueo {
+                cabana_id: cabana.id,
+                fecha: entrada.fecha,
+                hora_inicio: entrada.hora_inicio,
+                hora_fin: entrada.hora_fin,
+                nota: Some(format!("Oferta de lista de espera #{}", oferta.id)),
+            },
+            oferta.vence,
+            Some(oferta.id),
+        )?;
+        diesel::update(lista_espera::table.find(entrada.id))
+            .set(lista_espera::estado.eq(EstadoEspera::Ofertada))
+            .execute(conn)?;
+        ofertas.push(oferta);
+    }
+
+    Ok(ofertas)
+}
+
+/// Cierra la oferta, suelta su bloqueo y vuelve a ofrecer su horario en la misma cabaña
+fn cerrar_y_reofrecer(
+    conn: &mut PgConnection,
+    oferta: OfertaEspera,
+    estado: EstadoOferta,
+    entrada: &EntradaEspera,
+) -> ApiResult<Vec<OfertaEspera>> {
+    use crate::services::bloqueos_service;
+
+    diesel::update(ofertas_espera::table.find(oferta.id))
+        .set(ofertas_espera::estado.eq(estado))
+        .execute(conn)?;
+    bloqueos_service::soltar_oferta(conn, oferta.id)?;
+    ofrecer_hueco(conn, oferta.cabana_id, entrada.inicio, entrada.fin)
+}
+
+fn ya_la_descarto(conn: &mut PgConnection, espera_id: i32, cabana_id: i32) -> QueryResult<bool> {
+    diesel::select(diesel::dsl::exists(
+        ofertas_espera::table
+            .filter(ofertas_espera::espera_id.eq(espera_id))
+            .filter(ofertas_espera::cabana_id.eq(cabana_id))
+            .filter(ofertas_espera::estado.eq_any([EstadoOferta::Rechazada, EstadoOferta::Vencida])),
+    ))
+    .get_result(conn)
+}
+
+/// La entrada se podría reservar hoy en la cabaña. Los rechazos (409/422)
+/// significan "no encaja"; cualquier otro error se propaga
+fn admite(conn: &mut PgConnection, cabana: &Cabana, entrada: &EntradaEspera) -> ApiResult<bool> {
+    use crate::services::{horarios_service, mantenimientos_service, reglas_reserva_service, validaciones_service};
+
+    let resultado = validaciones_service::validar_personas(cabana, entrada.num_personas)
+        .and_then(|_| {
+            reglas_reserva_service::validar_reserva(
+                conn,
+                cabana.id,
+                entrada.fecha,
+                entrada.inicio,
+                entrada.fin,
+                true,
+                true,
+            )
+        })
+        .and_then(|_| horarios_service::validar_en_horario(conn, entrada.inicio, entrada.fin))
+        .and_then(|_| {
+            mantenimientos_service::validar_fuera_de_mantenimiento(conn, cabana.id, entrada.inicio, entrada.fin)
+        })
+        .and_then(|_| validaciones_service::validar_conflictos(conn, cabana, entrada.inicio, entrada.fin, None, false));
+
+    match resultado {
+        Ok(()) => Ok(true),
+        Err(e) if e.es_rechazo() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn buscar(conn: &mut PgConnection, espera_id: i32) -> ApiResult<EntradaEspera> {
+    lista_espera::table
+        .find(espera_id)
+        .for_update()
+        .first::<EntradaEspera>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Entrada {} de la lista de espera no encontrada", espera_id)))
+}
+
+/// 409 si la oferta ya se cerró o venció su plazo
+fn oferta_pendiente(conn: &mut PgConnection, oferta_id: i32) -> ApiResult<OfertaEspera> {
+    let oferta = ofertas_espera::table
+        .find(oferta_id)
+        .for_update()
+        .first::<OfertaEspera>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Oferta {} no encontrada", oferta_id)))?;
+
+    if oferta.estado != EstadoOferta::Pendiente {
+        return Err(ApiError::conflicto(format!("La oferta {} ya está {}", oferta.id, oferta.estado.as_str()))
+            .con_detalles(serde_json::json!({ "estado": oferta.estado })));
+    }
+    if oferta.vence <= Local::now().naive_local() {
+        return Err(ApiError::conflicto(format!("La oferta {} venció", oferta.id))
+            .con_detalles(serde_json::json!({ "vence": oferta.vence })));
+    }
+    Ok(oferta)
+}
@@ -0,0 +1,170 @@
+use chrono::{Local, NaiveDateTime};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    DetalleMantenimiento, EstadoReserva, FiltroMantenimientos, Intervalo, Mantenimiento, NewMantenimiento,
+    Reserva, UpdateMantenimiento,
+};
+use crate::schema::{mantenimientos, reservas};
+
+// =============================
+// 🛠️ CRUD de ventanas de mantenimiento
+//  - Crear o mover una ventana nunca falla por las reservas que pisa:
+//    se devuelven en `reservas_afectadas` para reubicarlas
+// =============================
+pub fn listar(conn: &mut PgConnection, filtro: FiltroMantenimientos) -> ApiResult<Vec<Mantenimiento>> {
+    use crate::services::validaciones_service::parsear_fecha;
+
+    let desde = match filtro.desde.as_deref() {
+        Some(v) => parsear_fecha("desde", v)?,
+        None => Local::now().date_naive(),
+    };
+
+    let mut query = mantenimientos::table
+        .filter(mantenimientos::fin.gt(desde.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        .order((mantenimientos::inicio.asc(), mantenimientos::id.asc()))
+        .into_boxed();
+
+    if let Some(id) = filtro.cabana_id {
+        query = query.filter(mantenimientos::cabana_id.eq(id));
+    }
+
+    Ok(query.load::<Mantenimiento>(conn)?)
+}
+
+pub fn obtener(conn: &mut PgConnection, mantenimiento_id: i32) -> ApiResult<DetalleMantenimiento> {
+    let mantenimiento = mantenimientos::table
+        .find(mantenimiento_id)
+        .first::<Mantenimiento>(conn)
+        .optional()?
+        .ok_or_else(|| no_encontrado(mantenimiento_id))?;
+    detalle(conn, mantenimiento)
+}
+
+pub fn crear(conn: &mut PgConnection, nuevo: NewMantenimiento) -> ApiResult<DetalleMantenimiento> {
+    use crate::services::cabanas_service;
+
+    validar(nuevo.inicio, nuevo.fin, &nuevo.motivo)?;
+    cabanas_service::obtener_cabana(conn, nuevo.cabana_id)?;
+
+    conn.transaction::<DetalleMantenimiento, ApiError, _>(|conn| {
+        let mantenimiento = diesel::insert_into(mantenimientos::table)
+            .values(&nuevo)
+            .get_result::<Mantenimiento>(conn)?;
+        detalle(conn, mantenimiento)
+    })
+}
+
+pub fn actualizar(
+    conn: &mut PgConnection,
+    mantenimiento_id: i32,
+    cambios: UpdateMantenimiento,
+) -> ApiResult<DetalleMantenimiento> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+
+    conn.transaction::<DetalleMantenimiento, ApiError, _>(|conn| {
+        // Se valida la ventana resultante antes de que el CHECK de la BD la rechace
+        let actual = mantenimientos::table
+            .find(mantenimiento_id)
+            .for_update()
+            .first::<Mantenimiento>(conn)
+            .optional()?
+            .ok_or_else(|| no_encontrado(mantenimiento_id))?;
+        validar(
+            cambios.inicio.unwrap_or(actual.inicio),
+            cambios.fin.unwrap_or(actual.fin),
+            cambios.motivo.as_deref().unwrap_or(&actual.motivo),
+        )?;
+
+        let mantenimiento = diesel::update(mantenimientos::table.find(mantenimiento_id))
+            .set(&cambios)
+            .get_result::<Mantenimiento>(conn)?;
+        detalle(conn, mantenimiento)
+    })
+}
+
+pub fn eliminar(conn: &mut PgConnection, mantenimiento_id: i32) -> ApiResult<()> {
+    let borrados = diesel::delete(mantenimientos::table.find(mantenimiento_id)).execute(conn)?;
+    if borrados == 0 {
+        return Err(no_encontrado(mantenimiento_id));
+    }
+    Ok(())
+}
+
+fn validar(inicio: NaiveDateTime, fin: NaiveDateTime, motivo: &str) -> ApiResult<()> {
+    if fin <= inicio {
+        return Err(ApiError::validacion("El mantenimiento debe terminar después de empezar")
+            .con_detalles(serde_json::json!({ "campo": "fin" })));
+    }
+    if motivo.trim().is_empty() {
+        return Err(ApiError::validacion("El motivo del mantenimiento es obligatorio")
+            .con_detalles(serde_json::json!({ "campo": "motivo" })));
+    }
+    Ok(())
+}
+
+fn no_encontrado(mantenimiento_id: i32) -> ApiError {
+    ApiError::no_encontrado(format!("Mantenimiento {} no encontrado", mantenimiento_id))
+}
+
+/// Reservas todavía por cumplirse de la cabaña que se solapan con la ventana
+fn detalle(conn: &mut PgConnection, mantenimiento: Mantenimiento) -> ApiResult<DetalleMantenimiento> {
+    let reservas_afectadas = reservas::table
+        .filter(reservas::cabana_id.eq(mantenimiento.cabana_id))
+        .filter(reservas::estado.ne_all(EstadoReserva::FINALES))
+        .filter(reservas::inicio.lt(mantenimiento.fin))
+        .filter(reservas::fin.gt(mantenimiento.inicio))
+        .order(reservas::inicio.asc())
+        .load::<Reserva>(conn)?;
+
+    Ok(DetalleMantenimiento { mantenimiento, reservas_afectadas })
+}
+
+// =============================
+// 🚧 Consultas para reservas y disponibilidad
+// =============================
+
+/// ⚠️ 409 si `[inicio, fin)` pisa una ventana de mantenimiento de la cabaña.
+/// A diferencia del margen de limpieza, no se puede forzar.
+pub fn validar_fuera_de_mantenimiento(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+) -> ApiResult<()> {
+    let ventanas = mantenimientos::table
+        .filter(mantenimientos::cabana_id.eq(cabana_id))
+        .filter(mantenimientos::inicio.lt(fin))
+        .filter(mantenimientos::fin.gt(inicio))
+        .order(mantenimientos::inicio.asc())
+        .load::<Mantenimiento>(conn)?;
+
+    if ventanas.is_empty() {
+        return Ok(());
+    }
+    Err(ApiError::conflicto("⚠️ La cabaña está en mantenimiento en ese horario.")
+        .con_detalles(serde_json::json!({ "mantenimientos": ventanas })))
+}
+
+/// Ventanas por cabaña que tocan `ventana`, para restarlas de los huecos libres
+pub fn ventanas_por_cabana(
+    conn: &mut PgConnection,
+    cabana_ids: &[i32],
+    ventana: Intervalo,
+) -> QueryResult<HashMap<i32, Vec<Intervalo>>> {
+    let filas = mantenimientos::table
+        .filter(mantenimientos::cabana_id.eq_any(cabana_ids))
+        .filter(mantenimientos::inicio.lt(ventana.fin))
+        .filter(mantenimientos::fin.gt(ventana.inicio))
+        .select((mantenimientos::cabana_id, mantenimientos::inicio, mantenimientos::fin))
+        .load::<(i32, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    let mut por_cabana: HashMap<i32, Vec<Intervalo>> = HashMap::new();
+    for (cabana_id, inicio, fin) in filas {
+        por_cabana.entry(cabana_id).or_default().push(Intervalo { inicio, fin });
+    }
+    Ok(por_cabana)
+}
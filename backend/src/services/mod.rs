@@ -1,4 +1,17 @@
+pub mod bloqueos_service;
 pub mod cabanas_service;
 pub mod clientes_service;
+pub mod comprobantes_service;
+pub mod configuracion_service;
+pub mod disponibilidad_service;
+pub mod horarios_service;
+pub mod lista_espera_service;
+pub mod mantenimientos_service;
+pub mod pagos_service;
+pub mod precios_service;
+pub mod promociones_service;
+pub mod reglas_precio_service;
+pub mod reglas_reserva_service;
 pub mod reservas_service;
+pub mod series_service;
 pub mod validaciones_service;
@@ -0,0 +1,107 @@
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{EstadoCuenta, EstadoReserva, NewPago, Pago, Reserva, SolicitudPago, TipoPago};
+use crate::schema::{pagos, reservas};
+
+// =============================
+// 📒 Estado de cuenta de una reserva
+// =============================
+pub fn estado_cuenta(conn: &mut PgConnection, reserva_id: i32) -> ApiResult<EstadoCuenta> {
+    let reserva = reservas::table
+        .find(reserva_id)
+        .first::<Reserva>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+    let pagos = pagos::table
+        .filter(pagos::reserva_id.eq(reserva_id))
+        .order((pagos::fecha_pago.asc(), pagos::id.asc()))
+        .load::<Pago>(conn)?;
+
+    Ok(EstadoCuenta {
+        reserva_id,
+        monto_total: reserva.monto_total,
+        monto_pagado: reserva.monto_pagado,
+        saldo: reserva.saldo,
+        pagos,
+    })
+}
+
+// =============================
+// 💵 Registrar pago o reembolso
+//  - La reserva se bloquea (FOR UPDATE) para que dos cobros simultáneos
+//    no se pisen al acumular monto_pagado
+//  - No se cobra más que el saldo ni se devuelve más de lo pagado
+// =============================
+pub fn registrar_movimiento(
+    conn: &mut PgConnection,
+    reserva_id: i32,
+    tipo: TipoPago,
+    solicitud: SolicitudPago,
+) -> ApiResult<(Pago, Reserva)> {
+    let cero = BigDecimal::from(0);
+    if solicitud.monto <= cero {
+        return Err(ApiError::validacion("El monto debe ser mayor que cero")
+            .con_detalles(serde_json::json!({ "campo": "monto" })));
+    }
+    if solicitud.registrado_por.trim().is_empty() {
+        return Err(ApiError::validacion("Indica qué usuario registra el movimiento")
+            .con_detalles(serde_json::json!({ "campo": "registrado_por" })));
+    }
+    let monto = solicitud.monto.with_scale_round(2, bigdecimal::RoundingMode::HalfUp);
+
+    conn.transaction::<(Pago, Reserva), ApiError, _>(|conn| {
+        let reserva = reservas::table
+            .find(reserva_id)
+            .for_update()
+            .first::<Reserva>(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+        let nuevo_pagado = match tipo {
+            TipoPago::Pago => {
+                if EstadoReserva::LIBERAN_CABANA.contains(&reserva.estado) {
+                    return Err(ApiError::conflicto(format!(
+                        "La reserva {} está {} y no admite pagos",
+                        reserva.id, reserva.estado
+                    )));
+                }
+                if let Some(saldo) = &reserva.saldo {
+                    if &monto > saldo {
+                        return Err(ApiError::validacion("El pago supera el saldo pendiente")
+                            .con_detalles(serde_json::json!({ "campo": "monto", "saldo": saldo })));
+                    }
+                }
+                &reserva.monto_pagado + &monto
+            }
+            TipoPago::Reembolso => {
+                if monto > reserva.monto_pagado {
+                    return Err(ApiError::validacion("El reembolso supera lo pagado")
+                        .con_detalles(serde_json::json!({
+                            "campo": "monto",
+                            "monto_pagado": reserva.monto_pagado,
+                        })));
+                }
+                &reserva.monto_pagado - &monto
+            }
+        };
+
+        let pago = diesel::insert_into(pagos::table)
+            .values(&NewPago {
+                reserva_id: reserva.id,
+                tipo,
+                monto,
+                metodo: solicitud.metodo,
+                referencia: solicitud.referencia,
+                registrado_por: solicitud.registrado_por.trim().to_string(),
+            })
+            .get_result::<Pago>(conn)?;
+
+        let reserva = diesel::update(reservas::table.find(reserva.id))
+            .set(reservas::monto_pagado.eq(nuevo_pagado))
+            .get_result::<Reserva>(conn)?;
+
+        Ok((pago, reserva))
+    })
+}
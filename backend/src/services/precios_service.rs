@@ -0,0 +1,356 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    Cabana, Cotizacion, DiaTarifas, ModoReglaPrecio, ReglaPrecio, SolicitudCotizacion, TramoPrecio, TramoTarifa,
+};
+use crate::services::reglas_precio_service::{self, ReglasCabana};
+
+/// Bloque de facturación: las fracciones de hora se cobran por bloques
+/// completos de 30 minutos (1h10 → 1h30, 2h31 → 3h)
+pub const BLOQUE_FACTURACION_MINUTOS: i64 = 30;
+
+// =============================
+// 🧾 Cotizar sin reservar (POST /reservas/cotizar)
+// =============================
+pub fn cotizar_solicitud(conn: &mut PgConnection, solicitud: SolicitudCotizacion) -> ApiResult<Cotizacion> {
+    use crate::services::{cabanas_service, promociones_service, validaciones_service};
+
+    validaciones_service::validar_horas(solicitud.hora_inicio, solicitud.hora_fin)?;
+
+    let cabana = cabanas_service::obtener_cabana_activa(conn, solicitud.cabana_id)?;
+    let (inicio, fin) =
+        validaciones_service::rango_reserva(solicitud.fecha_reserva, solicitud.hora_inicio, solicitud.hora_fin);
+
+    let mut cotizacion = cotizar(conn, &cabana, inicio, fin)?;
+
+    // Con código, se valida igual que al reservar (sin canjearlo)
+    if let Some(codigo) = solicitud.codigo_promocion.as_deref().filter(|c| !c.trim().is_empty()) {
+        let ctx = promociones_service::ContextoCanje {
+            cabana_id: cabana.id,
+            cliente_id: solicitud.cliente_id,
+            fecha: solicitud.fecha_reserva,
+            hora_inicio: solicitud.hora_inicio,
+            inicio,
+            fin,
+            excluir_reserva: None,
+        };
+        let (promo, descuento) = promociones_service::validar_codigo(conn, codigo, &ctx, &cotizacion.monto_total)?;
+        cotizacion.monto_total = &cotizacion.monto_total - &descuento;
+        cotizacion.descuento = descuento;
+        cotizacion.codigo_promocion = Some(promo.codigo);
+    }
+
+    Ok(cotizacion)
+}
+
+/// Monto a congelar en la reserva; `None` si algún tramo queda sin precio
+/// (cabaña sin precio_hora y sin una regla de tarifa que lo cubra)
+pub fn monto_reserva(
+    conn: &mut PgConnection,
+    cabana: &Cabana,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+) -> ApiResult<Option<BigDecimal>> {
+    let reglas = reglas_precio_service::reglas_de_cabana(conn, cabana, inicio.date(), fin.date())?;
+    Ok(desglosar(cabana, &reglas, inicio, fin).map(|c| c.monto_total))
+}
+
+/// 💰 Desglose del precio partido por las reglas vigentes en cada tramo
+pub fn cotizar(
+    conn: &mut PgConnection,
+    cabana: &Cabana,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+) -> ApiResult<Cotizacion> {
+    let reglas = reglas_precio_service::reglas_de_cabana(conn, cabana, inicio.date(), fin.date())?;
+    desglosar(cabana, &reglas, inicio, fin).ok_or_else(|| {
+        ApiError::validacion(format!("La cabaña {} no tiene precio por hora configurado", cabana.nombre))
+            .con_detalles(serde_json::json!({ "campo": "precio_hora" }))
+    })
+}
+
+/// Recorre la reserva minuto a minuto agrupando los minutos consecutivos con
+/// la misma regla y tarifa. Los minutos agregados por el redondeo al bloque
+/// se cobran con la tarifa del último tramo.
+fn desglosar(
+    cabana: &Cabana,
+    reglas: &ReglasCabana,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+) -> Option<Cotizacion> {
+    // Minutos iniciados cuentan como completos
+    let minutos = ((fin - inicio).num_seconds() + 59) / 60;
+    let minutos_facturados = redondear_a_bloque(minutos);
+
+    let mut tramos: Vec<Tramo> = Vec::new();
+    for k in 0..minutos {
+        let t = inicio + Duration::minutes(k);
+        let regla = reglas.regla_en(t);
+        let precio_hora = tarifa(cabana, regla)?;
+        let hasta = (t + Duration::minutes(1)).min(fin);
+
+        match tramos.last_mut() {
+            Some(ultimo) if ultimo.regla_id == regla.map(|r| r.id) && ultimo.precio_hora == precio_hora => {
+                ultimo.fin = hasta;
+                ultimo.minutos += 1;
+            }
+            _ => tramos.push(Tramo {
+                inicio: t,
+                fin: hasta,
+                minutos: 1,
+                precio_hora,
+                regla_id: regla.map(|r| r.id),
+                regla: regla.map(|r| r.nombre.clone()),
+            }),
+        }
+    }
+
+    if let Some(ultimo) = tramos.last_mut() {
+        ultimo.minutos += minutos_facturados - minutos;
+    }
+
+    let tramos: Vec<TramoPrecio> = tramos
+        .into_iter()
+        .map(|t| {
+            let horas = BigDecimal::from(t.minutos) / BigDecimal::from(60);
+            TramoPrecio {
+                inicio: t.inicio,
+                fin: t.fin,
+                subtotal: redondear_monto(&(&horas * &t.precio_hora)),
+                horas: horas.with_scale_round(2, RoundingMode::HalfUp),
+                precio_hora: t.precio_hora,
+                regla_id: t.regla_id,
+                regla: t.regla,
+            }
+        })
+        .collect();
+    let monto_total = tramos.iter().fold(BigDecimal::from(0), |acc, t| acc + &t.subtotal);
+
+    Some(Cotizacion {
+        cabana_id: cabana.id,
+        inicio,
+        fin,
+        minutos,
+        minutos_facturados,
+        tramos,
+        descuento: BigDecimal::from(0).with_scale(2),
+        codigo_promocion: None,
+        monto_total: redondear_monto(&monto_total),
+    })
+}
+
+/// Tramo en construcción (minutos sin convertir a horas)
+struct Tramo {
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+    minutos: i64,
+    precio_hora: BigDecimal,
+    regla_id: Option<i32>,
+    regla: Option<String>,
+}
+
+/// Precio por hora efectivo: la regla ganadora o el precio base de la cabaña
+fn tarifa(cabana: &Cabana, regla: Option<&ReglaPrecio>) -> Option<BigDecimal> {
+    match regla {
+        Some(r) if r.modo == ModoReglaPrecio::Tarifa => Some(r.valor.clone()),
+        Some(r) => cabana.precio_hora.as_ref().map(|p| redondear_monto(&(p * &r.valor))),
+        None => cabana.precio_hora.clone(),
+    }
+}
+
+// =============================
+// 🗓️ Grilla semanal de tarifas (GET /cabanas/<id>/tarifas)
+// =============================
+pub const DIAS_GRILLA: i64 = 7;
+
+pub fn grilla_tarifas(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    desde: Option<&str>,
+) -> ApiResult<Vec<DiaTarifas>> {
+    use crate::services::{cabanas_service, validaciones_service};
+
+    let cabana = cabanas_service::obtener_cabana(conn, cabana_id)?;
+    let primer_dia: NaiveDate = match desde {
+        Some(v) => validaciones_service::parsear_fecha("desde", v)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let ultimo_dia = primer_dia + Duration::days(DIAS_GRILLA - 1);
+    let reglas = reglas_precio_service::reglas_de_cabana(conn, &cabana, primer_dia, ultimo_dia)?;
+
+    Ok((0..DIAS_GRILLA)
+        .map(|d| {
+            let fecha = primer_dia + Duration::days(d);
+            let inicio_dia = fecha.and_time(NaiveTime::MIN);
+            let mut tramos: Vec<TramoTarifa> = Vec::new();
+
+            for m in 0..24 * 60 {
+                let t = inicio_dia + Duration::minutes(m);
+                let regla = reglas.regla_en(t);
+                let precio_hora = tarifa(&cabana, regla);
+                let hasta = t + Duration::minutes(1);
+
+                match tramos.last_mut() {
+                    Some(ultimo) if ultimo.regla_id == regla.map(|r| r.id) && ultimo.precio_hora == precio_hora => {
+                        ultimo.fin = hasta;
+                    }
+                    _ => tramos.push(TramoTarifa {
+                        inicio: t,
+                        fin: hasta,
+                        precio_hora,
+                        regla_id: regla.map(|r| r.id),
+                        regla: regla.map(|r| r.nombre.clone()),
+                    }),
+                }
+            }
+
+            DiaTarifas { fecha, tramos }
+        })
+        .collect())
+}
+
+/// Redondea hacia arriba al siguiente bloque de facturación
+fn redondear_a_bloque(minutos: i64) -> i64 {
+    let bloques = (minutos + BLOQUE_FACTURACION_MINUTOS - 1) / BLOQUE_FACTURACION_MINUTOS;
+    bloques * BLOQUE_FACTURACION_MINUTOS
+}
+
+/// Importes con 2 decimales, redondeo comercial
+fn redondear_monto(monto: &BigDecimal) -> BigDecimal {
+    monto.with_scale_round(2, RoundingMode::HalfUp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EstadoCabana;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    fn cabana(precio_hora: Option<i32>) -> Cabana {
+        Cabana {
+            id: 1,
+            nombre: "Cabaña 1".into(),
+            capacidad: 6,
+            ubicacion: None,
+            estado: EstadoCabana::Activa,
+            descripcion: None,
+            precio_hora: precio_hora.map(BigDecimal::from),
+            archivada: false,
+            ocupacion_minima: 1,
+            minutos_limpieza: None,
+            zona: None,
+        }
+    }
+
+    /// Multiplicador (o tarifa) entre las `desde` y las `hasta` de todos los días
+    fn regla(id: i32, modo: ModoReglaPrecio, valor: i32, desde: u32, hasta: u32) -> ReglaPrecio {
+        ReglaPrecio {
+            id,
+            nombre: format!("Regla {}", id),
+            cabana_id: None,
+            zona: None,
+            dias_semana: None,
+            hora_desde: NaiveTime::from_hms_opt(desde, 0, 0),
+            hora_hasta: NaiveTime::from_hms_opt(hasta, 0, 0),
+            fecha_desde: None,
+            fecha_hasta: None,
+            solo_feriados: false,
+            modo,
+            valor: BigDecimal::from(valor),
+            prioridad: 0,
+            activa: true,
+        }
+    }
+
+    fn reglas(cabana: &Cabana, candidatas: Vec<ReglaPrecio>) -> ReglasCabana {
+        ReglasCabana::nueva(cabana, candidatas, HashSet::new())
+    }
+
+    fn instante(hora: u32, minuto: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 23).unwrap().and_hms_opt(hora, minuto, 0).unwrap()
+    }
+
+    fn monto(valor: &str) -> BigDecimal {
+        BigDecimal::from_str(valor).unwrap()
+    }
+
+    #[test]
+    fn redondea_hacia_arriba_al_bloque() {
+        assert_eq!(redondear_a_bloque(0), 0);
+        assert_eq!(redondear_a_bloque(1), 30);
+        assert_eq!(redondear_a_bloque(30), 30);
+        assert_eq!(redondear_a_bloque(31), 60);
+        assert_eq!(redondear_a_bloque(70), 90);
+        assert_eq!(redondear_a_bloque(151), 180);
+    }
+
+    #[test]
+    fn sin_reglas_cobra_el_precio_base_redondeado() {
+        let c = cabana(Some(50));
+        let cotizacion = desglosar(&c, &reglas(&c, vec![]), instante(19, 0), instante(20, 10)).unwrap();
+
+        assert_eq!(cotizacion.minutos, 70);
+        assert_eq!(cotizacion.minutos_facturados, 90);
+        assert_eq!(cotizacion.tramos.len(), 1);
+        assert_eq!(cotizacion.tramos[0].horas, monto("1.50"));
+        assert_eq!(cotizacion.monto_total, monto("75.00"));
+    }
+
+    #[test]
+    fn los_segundos_iniciados_cuentan_como_minuto() {
+        let c = cabana(Some(60));
+        let fin = instante(20, 0) + Duration::seconds(1);
+        let cotizacion = desglosar(&c, &reglas(&c, vec![]), instante(19, 0), fin).unwrap();
+
+        assert_eq!(cotizacion.minutos, 61);
+        assert_eq!(cotizacion.minutos_facturados, 90);
+    }
+
+    #[test]
+    fn parte_la_reserva_en_tramos_por_regla() {
+        let c = cabana(Some(50));
+        let noche = regla(1, ModoReglaPrecio::Multiplicador, 2, 20, 22);
+        let cotizacion = desglosar(&c, &reglas(&c, vec![noche]), instante(19, 0), instante(21, 0)).unwrap();
+
+        assert_eq!(cotizacion.tramos.len(), 2);
+        let (base, noche) = (&cotizacion.tramos[0], &cotizacion.tramos[1]);
+        assert_eq!((base.inicio, base.fin, base.regla_id), (instante(19, 0), instante(20, 0), None));
+        assert_eq!(base.subtotal, monto("50.00"));
+        assert_eq!((noche.inicio, noche.fin, noche.regla_id), (instante(20, 0), instante(21, 0), Some(1)));
+        assert_eq!(noche.precio_hora, monto("100.00"));
+        assert_eq!(noche.subtotal, monto("100.00"));
+        assert_eq!(cotizacion.monto_total, monto("150.00"));
+    }
+
+    #[test]
+    fn el_redondeo_se_cobra_con_la_tarifa_del_ultimo_tramo() {
+        let c = cabana(Some(50));
+        // 19:00–20:10: 60 min a 50 y 10 min (facturados 30) con tarifa fija de 120
+        let noche = regla(1, ModoReglaPrecio::Tarifa, 120, 20, 22);
+        let cotizacion = desglosar(&c, &reglas(&c, vec![noche]), instante(19, 0), instante(20, 10)).unwrap();
+
+        assert_eq!(cotizacion.tramos.len(), 2);
+        assert_eq!(cotizacion.tramos[0].horas, monto("1.00"));
+        assert_eq!(cotizacion.tramos[1].horas, monto("0.50"));
+        assert_eq!(cotizacion.tramos[1].subtotal, monto("60.00"));
+        assert_eq!(cotizacion.monto_total, monto("110.00"));
+    }
+
+    #[test]
+    fn sin_precio_base_solo_cotiza_si_una_tarifa_lo_cubre() {
+        let c = cabana(None);
+        let tarifa = regla(1, ModoReglaPrecio::Tarifa, 80, 18, 22);
+        let multiplicador = regla(2, ModoReglaPrecio::Multiplicador, 2, 18, 22);
+
+        assert!(desglosar(&c, &reglas(&c, vec![]), instante(19, 0), instante(20, 0)).is_none());
+        assert!(desglosar(&c, &reglas(&c, vec![multiplicador]), instante(19, 0), instante(20, 0)).is_none());
+        // Se sale de la franja de la tarifa a las 22:00
+        assert!(desglosar(&c, &reglas(&c, vec![tarifa.clone()]), instante(21, 0), instante(22, 30)).is_none());
+
+        let cotizacion = desglosar(&c, &reglas(&c, vec![tarifa]), instante(19, 0), instante(20, 0)).unwrap();
+        assert_eq!(cotizacion.monto_total, monto("80.00"));
+    }
+}
@@ -0,0 +1,252 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    CanjePromocion, EstadoReserva, NewCanjePromocion, NewPromocion, Promocion, TipoDescuento, UpdatePromocion,
+};
+use crate::schema::{canjes_promocion, promociones, reservas};
+
+// =============================
+// 🏷️ CRUD de promociones
+// =============================
+pub fn listar_promociones(conn: &mut PgConnection) -> QueryResult<Vec<Promocion>> {
+    promociones::table
+        .order(promociones::id.asc())
+        .load::<Promocion>(conn)
+}
+
+pub fn obtener_promocion(conn: &mut PgConnection, promocion_id: i32) -> ApiResult<Promocion> {
+    promociones::table
+        .find(promocion_id)
+        .first::<Promocion>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Promoción {} no encontrada", promocion_id)))
+}
+
+pub fn crear_promocion(conn: &mut PgConnection, mut nueva: NewPromocion) -> ApiResult<Promocion> {
+    nueva.codigo = normalizar_codigo(&nueva.codigo);
+    if nueva.codigo.is_empty() {
+        return Err(ApiError::validacion("El código de la promoción es obligatorio")
+            .con_detalles(serde_json::json!({ "campo": "codigo" })));
+    }
+
+    conn.transaction::<Promocion, ApiError, _>(|conn| {
+        let promo = diesel::insert_into(promociones::table)
+            .values(&nueva)
+            .get_result::<Promocion>(conn)?;
+        validar_promocion(&promo)?;
+        Ok(promo)
+    })
+}
+
+pub fn actualizar_promocion(
+    conn: &mut PgConnection,
+    promocion_id: i32,
+    cambios: UpdatePromocion,
+) -> ApiResult<Promocion> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+
+    conn.transaction::<Promocion, ApiError, _>(|conn| {
+        let promo = diesel::update(promociones::table.find(promocion_id))
+            .set(&cambios)
+            .get_result::<Promocion>(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Promoción {} no encontrada", promocion_id)))?;
+        validar_promocion(&promo)?;
+        Ok(promo)
+    })
+}
+
+/// Una promoción ya canjeada no se borra (se perdería el historial): se desactiva
+pub fn eliminar_promocion(conn: &mut PgConnection, promocion_id: i32) -> ApiResult<()> {
+    conn.transaction::<(), ApiError, _>(|conn| {
+        let promo = obtener_promocion(conn, promocion_id)?;
+
+        let canjes = canjes_promocion::table
+            .filter(canjes_promocion::promocion_id.eq(promo.id))
+            .count()
+            .get_result::<i64>(conn)?;
+        if canjes > 0 {
+            return Err(ApiError::conflicto(format!(
+                "La promoción {} ya fue canjeada; desactívala en lugar de eliminarla",
+                promo.codigo
+            ))
+            .con_detalles(serde_json::json!({ "canjes": canjes })));
+        }
+
+        diesel::delete(promociones::table.find(promo.id)).execute(conn)?;
+        Ok(())
+    })
+}
+
+fn validar_promocion(promo: &Promocion) -> ApiResult<()> {
+    if let Some(dias) = &promo.dias_semana {
+        if dias.is_empty() || dias.iter().any(|d| !(1..=7).contains(d)) {
+            return Err(ApiError::validacion("dias_semana debe contener valores de 1 (lunes) a 7 (domingo)")
+                .con_detalles(serde_json::json!({ "campo": "dias_semana" })));
+        }
+    }
+    if matches!(&promo.cabana_ids, Some(ids) if ids.is_empty()) {
+        return Err(ApiError::validacion("cabana_ids no puede estar vacío (omítelo para todas las cabañas)")
+            .con_detalles(serde_json::json!({ "campo": "cabana_ids" })));
+    }
+    if matches!((promo.hora_desde, promo.hora_hasta), (Some(d), Some(h)) if d == h) {
+        return Err(ApiError::validacion("La franja horaria no puede empezar y terminar a la misma hora")
+            .con_detalles(serde_json::json!({ "campo": "hora_hasta" })));
+    }
+    Ok(())
+}
+
+fn normalizar_codigo(codigo: &str) -> String {
+    codigo.trim().to_uppercase()
+}
+
+// =============================
+// 🎟️ Validar y canjear códigos
+// =============================
+
+/// Reserva (real o cotizada) contra la que se valida un código
+pub struct ContextoCanje {
+    pub cabana_id: i32,
+    pub cliente_id: Option<i32>,
+    pub fecha: NaiveDate,
+    pub hora_inicio: NaiveTime,
+    pub inicio: NaiveDateTime,
+    pub fin: NaiveDateTime,
+    /// Al reprogramar, el canje propio no cuenta para los límites de uso
+    pub excluir_reserva: Option<i32>,
+}
+
+/// Comprueba que el código se pueda usar en esta reserva y calcula el
+/// descuento sobre `monto`. La fila de la promoción queda bloqueada hasta el
+/// final de la transacción: dos canjes simultáneos no superan el límite de usos.
+pub fn validar_codigo(
+    conn: &mut PgConnection,
+    codigo: &str,
+    ctx: &ContextoCanje,
+    monto: &BigDecimal,
+) -> ApiResult<(Promocion, BigDecimal)> {
+    let codigo = normalizar_codigo(codigo);
+
+    let promo = promociones::table
+        .filter(promociones::codigo.eq(&codigo))
+        .for_update()
+        .first::<Promocion>(conn)
+        .optional()?
+        .ok_or_else(|| rechazo(&codigo, "no existe"))?;
+
+    if !promo.activa {
+        return Err(rechazo(&codigo, "no está activa"));
+    }
+    if matches!(promo.vigente_desde, Some(d) if ctx.fecha < d) {
+        return Err(rechazo(&codigo, "todavía no está vigente para esa fecha"));
+    }
+    if matches!(promo.vigente_hasta, Some(h) if ctx.fecha > h) {
+        return Err(rechazo(&codigo, "ya no está vigente para esa fecha"));
+    }
+    if let Some(dias) = &promo.dias_semana {
+        if !dias.contains(&(ctx.fecha.weekday().number_from_monday() as i32)) {
+            return Err(rechazo(&codigo, "no vale ese día de la semana"));
+        }
+    }
+    if let (Some(desde), Some(hasta)) = (promo.hora_desde, promo.hora_hasta) {
+        let hora = ctx.hora_inicio;
+        let en_franja = if desde < hasta {
+            hora >= desde && hora < hasta
+        } else {
+            hora >= desde || hora < hasta
+        };
+        if !en_franja {
+            return Err(rechazo(&codigo, "no vale en ese horario"));
+        }
+    }
+    if matches!(&promo.cabana_ids, Some(ids) if !ids.contains(&ctx.cabana_id)) {
+        return Err(rechazo(&codigo, "no vale para esa cabaña"));
+    }
+    if let Some(minimas) = &promo.horas_minimas {
+        let horas = BigDecimal::from((ctx.fin - ctx.inicio).num_minutes()) / BigDecimal::from(60);
+        if &horas < minimas {
+            return Err(rechazo(&codigo, &format!("exige reservar al menos {} horas", minimas)));
+        }
+    }
+
+    if let Some(maximo) = promo.usos_maximos {
+        if usos(conn, promo.id, None, ctx.excluir_reserva)? >= maximo as i64 {
+            return Err(rechazo(&codigo, "está agotado"));
+        }
+    }
+    if let (Some(maximo), Some(cliente_id)) = (promo.usos_por_cliente, ctx.cliente_id) {
+        if usos(conn, promo.id, Some(cliente_id), ctx.excluir_reserva)? >= maximo as i64 {
+            return Err(rechazo(&codigo, "ya fue usado el máximo de veces por este cliente"));
+        }
+    }
+
+    let descuento = calcular_descuento(&promo, monto);
+    Ok((promo, descuento))
+}
+
+/// Registra (o actualiza, al reprogramar) el canje de la reserva
+pub fn registrar_canje(
+    conn: &mut PgConnection,
+    promocion_id: i32,
+    reserva_id: i32,
+    cliente_id: i32,
+    descuento: BigDecimal,
+) -> QueryResult<CanjePromocion> {
+    diesel::insert_into(canjes_promocion::table)
+        .values(&NewCanjePromocion { promocion_id, reserva_id, cliente_id, descuento: descuento.clone() })
+        .on_conflict(canjes_promocion::reserva_id)
+        .do_update()
+        .set(canjes_promocion::descuento.eq(descuento))
+        .get_result::<CanjePromocion>(conn)
+}
+
+/// Código canjeado por una reserva, si tiene alguno
+pub fn codigo_canjeado(conn: &mut PgConnection, reserva_id: i32) -> QueryResult<Option<String>> {
+    canjes_promocion::table
+        .inner_join(promociones::table)
+        .filter(canjes_promocion::reserva_id.eq(reserva_id))
+        .select(promociones::codigo)
+        .first::<String>(conn)
+        .optional()
+}
+
+/// Canjes que cuentan para los límites: los de reservas canceladas o no_show
+/// devuelven el uso
+fn usos(
+    conn: &mut PgConnection,
+    promocion_id: i32,
+    cliente_id: Option<i32>,
+    excluir_reserva: Option<i32>,
+) -> QueryResult<i64> {
+    let mut query = canjes_promocion::table
+        .inner_join(reservas::table)
+        .filter(canjes_promocion::promocion_id.eq(promocion_id))
+        .filter(reservas::estado.ne_all(EstadoReserva::LIBERAN_CABANA))
+        .into_boxed();
+
+    if let Some(id) = cliente_id {
+        query = query.filter(canjes_promocion::cliente_id.eq(id));
+    }
+    if let Some(id) = excluir_reserva {
+        query = query.filter(canjes_promocion::reserva_id.ne(id));
+    }
+
+    query.count().get_result::<i64>(conn)
+}
+
+fn calcular_descuento(promo: &Promocion, monto: &BigDecimal) -> BigDecimal {
+    let descuento = match promo.tipo {
+        TipoDescuento::Porcentaje => monto * &promo.valor / BigDecimal::from(100),
+        TipoDescuento::MontoFijo => promo.valor.clone(),
+    };
+    descuento.min(monto.clone()).with_scale_round(2, RoundingMode::HalfUp)
+}
+
+fn rechazo(codigo: &str, motivo: &str) -> ApiError {
+    ApiError::validacion(format!("El código {} {}", codigo, motivo))
+        .con_detalles(serde_json::json!({ "campo": "codigo_promocion", "codigo": codigo }))
+}
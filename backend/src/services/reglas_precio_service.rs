@@ -0,0 +1,409 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use diesel::prelude::*;
+use std::collections::HashSet;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{Cabana, Feriado, NewReglaPrecio, ReglaPrecio, UpdateReglaPrecio};
+use crate::schema::{feriados, reglas_precio};
+
+// =============================
+// 📈 CRUD de reglas de precio
+// =============================
+pub fn listar_reglas(conn: &mut PgConnection, cabana_id: Option<i32>) -> QueryResult<Vec<ReglaPrecio>> {
+    let mut query = reglas_precio::table
+        .order((reglas_precio::prioridad.desc(), reglas_precio::id.asc()))
+        .into_boxed();
+
+    if let Some(id) = cabana_id {
+        query = query.filter(reglas_precio::cabana_id.eq(id));
+    }
+
+    query.load::<ReglaPrecio>(conn)
+}
+
+pub fn obtener_regla(conn: &mut PgConnection, regla_id: i32) -> ApiResult<ReglaPrecio> {
+    reglas_precio::table
+        .find(regla_id)
+        .first::<ReglaPrecio>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Regla de precio {} no encontrada", regla_id)))
+}
+
+pub fn crear_regla(conn: &mut PgConnection, nueva: NewReglaPrecio) -> ApiResult<ReglaPrecio> {
+    use crate::services::cabanas_service;
+
+    validar_regla(&nueva.nombre, nueva.dias_semana.as_deref(), nueva.hora_desde, nueva.hora_hasta)?;
+    if let Some(cabana_id) = nueva.cabana_id {
+        cabanas_service::obtener_cabana(conn, cabana_id)?;
+    }
+    if let Some(zona) = nueva.zona.as_deref() {
+        validar_zona(conn, zona)?;
+    }
+
+    Ok(diesel::insert_into(reglas_precio::table)
+        .values(&nueva)
+        .get_result::<ReglaPrecio>(conn)?)
+}
+
+/// El alcance (cabaña / zona) no se edita: para moverla, se borra y se crea otra
+pub fn actualizar_regla(
+    conn: &mut PgConnection,
+    regla_id: i32,
+    cambios: UpdateReglaPrecio,
+) -> ApiResult<ReglaPrecio> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+
+    conn.transaction::<ReglaPrecio, ApiError, _>(|conn| {
+        let regla = diesel::update(reglas_precio::table.find(regla_id))
+            .set(&cambios)
+            .get_result::<ReglaPrecio>(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Regla de precio {} no encontrada", regla_id)))?;
+        // Se valida la regla resultante; si no es válida, la transacción se revierte
+        validar_regla(&regla.nombre, regla.dias_semana.as_deref(), regla.hora_desde, regla.hora_hasta)?;
+        Ok(regla)
+    })
+}
+
+pub fn eliminar_regla(conn: &mut PgConnection, regla_id: i32) -> ApiResult<()> {
+    let borradas = diesel::delete(reglas_precio::table.find(regla_id)).execute(conn)?;
+    if borradas == 0 {
+        return Err(ApiError::no_encontrado(format!("Regla de precio {} no encontrada", regla_id)));
+    }
+    Ok(())
+}
+
+/// La zona tiene que ser la de alguna cabaña: un error de tipeo dejaría la
+/// regla sin efecto sin que nadie lo note
+fn validar_zona(conn: &mut PgConnection, zona: &str) -> ApiResult<()> {
+    use crate::schema::cabanas;
+
+    if zona.trim().is_empty() {
+        return Err(ApiError::validacion("La zona no puede estar vacía")
+            .con_detalles(serde_json::json!({ "campo": "zona" })));
+    }
+    let zonas = cabanas::table
+        .filter(cabanas::zona.is_not_null())
+        .select(cabanas::zona.assume_not_null())
+        .distinct()
+        .order(cabanas::zona.asc())
+        .load::<String>(conn)?;
+    if !zonas.iter().any(|z| z == zona) {
+        return Err(ApiError::validacion(format!("Ninguna cabaña está en la zona '{}'", zona))
+            .con_detalles(serde_json::json!({ "campo": "zona", "zonas": zonas })));
+    }
+    Ok(())
+}
+
+/// Reglas que la BD no puede expresar con CHECK de forma legible
+fn validar_regla(
+    nombre: &str,
+    dias_semana: Option<&[i32]>,
+    hora_desde: Option<NaiveTime>,
+    hora_hasta: Option<NaiveTime>,
+) -> ApiResult<()> {
+    if nombre.trim().is_empty() {
+        return Err(ApiError::validacion("El nombre de la regla es obligatorio")
+            .con_detalles(serde_json::json!({ "campo": "nombre" })));
+    }
+    if let Some(dias) = dias_semana {
+        if dias.is_empty() || dias.iter().any(|d| !(1..=7).contains(d)) {
+            return Err(ApiError::validacion("dias_semana debe contener valores de 1 (lunes) a 7 (domingo)")
+                .con_detalles(serde_json::json!({ "campo": "dias_semana" })));
+        }
+    }
+    match (hora_desde, hora_hasta) {
+        (Some(desde), Some(hasta)) if desde == hasta => {
+            Err(ApiError::validacion("La franja horaria no puede empezar y terminar a la misma hora")
+                .con_detalles(serde_json::json!({ "campo": "hora_hasta" })))
+        }
+        (Some(_), None) | (None, Some(_)) => {
+            Err(ApiError::validacion("hora_desde y hora_hasta deben enviarse juntas")
+                .con_detalles(serde_json::json!({ "campo": "hora_hasta" })))
+        }
+        _ => Ok(()),
+    }
+}
+
+// =============================
+// 🎉 Feriados
+// =============================
+pub fn listar_feriados(conn: &mut PgConnection) -> QueryResult<Vec<Feriado>> {
+    feriados::table.order(feriados::fecha.asc()).load::<Feriado>(conn)
+}
+
+pub fn crear_feriado(conn: &mut PgConnection, nuevo: Feriado) -> QueryResult<Feriado> {
+    diesel::insert_into(feriados::table)
+        .values(&nuevo)
+        .get_result::<Feriado>(conn)
+}
+
+pub fn eliminar_feriado(conn: &mut PgConnection, fecha: NaiveDate) -> ApiResult<()> {
+    let borrados = diesel::delete(feriados::table.find(fecha)).execute(conn)?;
+    if borrados == 0 {
+        return Err(ApiError::no_encontrado(format!("No hay feriado cargado el {}", fecha)));
+    }
+    Ok(())
+}
+
+// =============================
+// 🧮 Resolución de la regla vigente
+// =============================
+
+/// Reglas activas que alcanzan a la cabaña y días feriados del rango,
+/// listas para consultar minuto a minuto con `regla_en`
+pub struct ReglasCabana {
+    reglas: Vec<ReglaPrecio>,
+    feriados: HashSet<NaiveDate>,
+}
+
+pub fn reglas_de_cabana(
+    conn: &mut PgConnection,
+    cabana: &Cabana,
+    desde: NaiveDate,
+    hasta: NaiveDate,
+) -> QueryResult<ReglasCabana> {
+    let candidatas = reglas_precio::table
+        .filter(reglas_precio::activa.eq(true))
+        .filter(
+            reglas_precio::cabana_id
+                .eq(cabana.id)
+                .or(reglas_precio::cabana_id.is_null()),
+        )
+        .load::<ReglaPrecio>(conn)?;
+
+    // Las franjas que cruzan la medianoche pertenecen al día anterior
+    let feriados = feriados::table
+        .filter(feriados::fecha.between(desde - Duration::days(1), hasta))
+        .select(feriados::fecha)
+        .load::<NaiveDate>(conn)?
+        .into_iter()
+        .collect();
+
+    Ok(ReglasCabana::nueva(cabana, candidatas, feriados))
+}
+
+impl ReglasCabana {
+    /// Se queda con las reglas de la zona de la cabaña (o sin zona) y las
+    /// ordena por precedencia: prioridad, luego especificidad
+    /// (cabaña > zona > todas), luego la más reciente
+    pub(crate) fn nueva(cabana: &Cabana, candidatas: Vec<ReglaPrecio>, feriados: HashSet<NaiveDate>) -> Self {
+        let mut reglas: Vec<ReglaPrecio> = candidatas
+            .into_iter()
+            .filter(|r| r.cabana_id.is_none_or(|id| id == cabana.id))
+            .filter(|r| r.zona.is_none() || r.zona == cabana.zona)
+            .collect();
+        reglas.sort_by_key(|r| {
+            let especificidad = if r.cabana_id.is_some() { 2 } else if r.zona.is_some() { 1 } else { 0 };
+            std::cmp::Reverse((r.prioridad, especificidad, r.id))
+        });
+        ReglasCabana { reglas, feriados }
+    }
+
+    /// Regla que gana en el instante `t`, si alguna aplica
+    pub fn regla_en(&self, t: NaiveDateTime) -> Option<&ReglaPrecio> {
+        self.reglas.iter().find(|r| self.aplica(r, t))
+    }
+
+    fn aplica(&self, regla: &ReglaPrecio, t: NaiveDateTime) -> bool {
+        let hoy = t.date();
+        let hora = t.time();
+
+        // Día "dueño" del instante según la franja de la regla
+        let dia = match (regla.hora_desde, regla.hora_hasta) {
+            (Some(desde), Some(hasta)) if desde < hasta => {
+                if hora < desde || hora >= hasta {
+                    return false;
+                }
+                hoy
+            }
+            (Some(desde), Some(hasta)) => {
+                if hora >= desde {
+                    hoy
+                } else if hora < hasta {
+                    hoy - Duration::days(1)
+                } else {
+                    return false;
+                }
+            }
+            _ => hoy,
+        };
+
+        if let Some(dias) = &regla.dias_semana {
+            if !dias.contains(&(dia.weekday().number_from_monday() as i32)) {
+                return false;
+            }
+        }
+        if matches!(regla.fecha_desde, Some(f) if dia < f) || matches!(regla.fecha_hasta, Some(f) if dia > f) {
+            return false;
+        }
+        !regla.solo_feriados || self.feriados.contains(&dia)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EstadoCabana, ModoReglaPrecio};
+    use bigdecimal::BigDecimal;
+    use chrono::NaiveTime;
+
+    fn cabana(zona: Option<&str>) -> Cabana {
+        Cabana {
+            id: 1,
+            nombre: "Cabaña 1".into(),
+            capacidad: 6,
+            ubicacion: Some("Zona A - Frente al escenario".into()),
+            estado: EstadoCabana::Activa,
+            descripcion: None,
+            precio_hora: Some(BigDecimal::from(50)),
+            archivada: false,
+            ocupacion_minima: 1,
+            minutos_limpieza: None,
+            zona: zona.map(String::from),
+        }
+    }
+
+    fn regla(id: i32) -> ReglaPrecio {
+        ReglaPrecio {
+            id,
+            nombre: format!("Regla {}", id),
+            cabana_id: None,
+            zona: None,
+            dias_semana: None,
+            hora_desde: None,
+            hora_hasta: None,
+            fecha_desde: None,
+            fecha_hasta: None,
+            solo_feriados: false,
+            modo: ModoReglaPrecio::Multiplicador,
+            valor: BigDecimal::from(2),
+            prioridad: 0,
+            activa: true,
+        }
+    }
+
+    fn franja(mut r: ReglaPrecio, desde: u32, hasta: u32) -> ReglaPrecio {
+        r.hora_desde = NaiveTime::from_hms_opt(desde, 0, 0);
+        r.hora_hasta = NaiveTime::from_hms_opt(hasta, 0, 0);
+        r
+    }
+
+    fn instante(dia: u32, hora: u32, minuto: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, dia).unwrap().and_hms_opt(hora, minuto, 0).unwrap()
+    }
+
+    fn ganadora(reglas: &ReglasCabana, t: NaiveDateTime) -> Option<i32> {
+        reglas.regla_en(t).map(|r| r.id)
+    }
+
+    // 2026-10-23 es viernes y 2026-10-24 sábado
+
+    #[test]
+    fn gana_la_de_mayor_prioridad() {
+        let mut alta = regla(1);
+        alta.prioridad = 5;
+        let mut de_cabana = regla(2);
+        de_cabana.cabana_id = Some(1);
+
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![de_cabana, alta], HashSet::new());
+        assert_eq!(ganadora(&reglas, instante(23, 12, 0)), Some(1));
+    }
+
+    #[test]
+    fn a_igual_prioridad_gana_la_mas_especifica_y_luego_la_mas_reciente() {
+        let general = regla(10);
+        let mut de_zona = regla(5);
+        de_zona.zona = Some("Zona A".into());
+        let mut de_cabana = regla(1);
+        de_cabana.cabana_id = Some(1);
+        let t = instante(23, 12, 0);
+
+        let reglas = ReglasCabana::nueva(
+            &cabana(Some("Zona A")),
+            vec![general.clone(), de_zona.clone(), de_cabana],
+            HashSet::new(),
+        );
+        assert_eq!(ganadora(&reglas, t), Some(1));
+
+        let reglas = ReglasCabana::nueva(&cabana(Some("Zona A")), vec![general.clone(), de_zona], HashSet::new());
+        assert_eq!(ganadora(&reglas, t), Some(5));
+
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![regla(3), general], HashSet::new());
+        assert_eq!(ganadora(&reglas, t), Some(10));
+    }
+
+    #[test]
+    fn la_zona_se_compara_exacta() {
+        let mut de_zona = regla(1);
+        de_zona.zona = Some("Terraza".into());
+        let t = instante(23, 12, 0);
+
+        for zona in [Some("Terraza norte"), Some("terraza"), None] {
+            let reglas = ReglasCabana::nueva(&cabana(zona), vec![de_zona.clone()], HashSet::new());
+            assert_eq!(ganadora(&reglas, t), None, "zona {:?}", zona);
+        }
+        let reglas = ReglasCabana::nueva(&cabana(Some("Terraza")), vec![de_zona], HashSet::new());
+        assert_eq!(ganadora(&reglas, t), Some(1));
+    }
+
+    #[test]
+    fn las_reglas_de_otra_cabana_no_aplican() {
+        let mut ajena = regla(1);
+        ajena.cabana_id = Some(2);
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![ajena], HashSet::new());
+        assert_eq!(ganadora(&reglas, instante(23, 12, 0)), None);
+    }
+
+    #[test]
+    fn franja_dentro_del_dia_es_semiabierta() {
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![franja(regla(1), 18, 22)], HashSet::new());
+        assert_eq!(ganadora(&reglas, instante(23, 17, 59)), None);
+        assert_eq!(ganadora(&reglas, instante(23, 18, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(23, 21, 59)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(23, 22, 0)), None);
+    }
+
+    #[test]
+    fn franja_que_cruza_la_medianoche_pertenece_al_dia_en_que_empieza() {
+        // Viernes 22:00–03:00
+        let mut viernes = franja(regla(1), 22, 3);
+        viernes.dias_semana = Some(vec![5]);
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![viernes], HashSet::new());
+
+        assert_eq!(ganadora(&reglas, instante(23, 21, 59)), None);
+        assert_eq!(ganadora(&reglas, instante(23, 23, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(24, 1, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(24, 3, 0)), None);
+        // La madrugada del viernes es del jueves
+        assert_eq!(ganadora(&reglas, instante(23, 1, 0)), None);
+        // El sábado a la noche no es viernes
+        assert_eq!(ganadora(&reglas, instante(24, 23, 0)), None);
+    }
+
+    #[test]
+    fn solo_feriados_mira_el_dia_dueno_de_la_franja() {
+        let mut feriado = franja(regla(1), 22, 3);
+        feriado.solo_feriados = true;
+        let feriados: HashSet<NaiveDate> = [NaiveDate::from_ymd_opt(2026, 10, 23).unwrap()].into();
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![feriado], feriados);
+
+        assert_eq!(ganadora(&reglas, instante(23, 23, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(24, 1, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(24, 23, 0)), None);
+    }
+
+    #[test]
+    fn rango_de_fechas_inclusivo() {
+        let mut temporada = regla(1);
+        temporada.fecha_desde = NaiveDate::from_ymd_opt(2026, 10, 20);
+        temporada.fecha_hasta = NaiveDate::from_ymd_opt(2026, 10, 23);
+        let reglas = ReglasCabana::nueva(&cabana(None), vec![temporada], HashSet::new());
+
+        assert_eq!(ganadora(&reglas, instante(19, 23, 59)), None);
+        assert_eq!(ganadora(&reglas, instante(20, 0, 0)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(23, 23, 59)), Some(1));
+        assert_eq!(ganadora(&reglas, instante(24, 0, 0)), None);
+    }
+}
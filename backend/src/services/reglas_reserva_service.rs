@@ -0,0 +1,269 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{LimitesReserva, NewReglaReserva, ReglaReserva, UpdateReglaReserva, Violacion};
+use crate::schema::reglas_reserva;
+
+// =============================
+// 📏 CRUD de reglas de reserva
+// =============================
+pub fn listar_reglas(conn: &mut PgConnection, cabana_id: Option<i32>) -> QueryResult<Vec<ReglaReserva>> {
+    let mut query = reglas_reserva::table
+        .order(reglas_reserva::id.asc())
+        .into_boxed();
+
+    if let Some(id) = cabana_id {
+        query = query.filter(reglas_reserva::cabana_id.eq(id));
+    }
+
+    query.load::<ReglaReserva>(conn)
+}
+
+pub fn crear_regla(conn: &mut PgConnection, nueva: NewReglaReserva) -> ApiResult<ReglaReserva> {
+    use crate::services::cabanas_service;
+
+    if let Some(cabana_id) = nueva.cabana_id {
+        cabanas_service::obtener_cabana(conn, cabana_id)?;
+    }
+    if let Some(dias) = &nueva.dias_semana {
+        validar_dias(dias)?;
+    }
+
+    Ok(diesel::insert_into(reglas_reserva::table)
+        .values(&nueva)
+        .get_result::<ReglaReserva>(conn)?)
+}
+
+pub fn actualizar_regla(
+    conn: &mut PgConnection,
+    regla_id: i32,
+    cambios: UpdateReglaReserva,
+) -> ApiResult<ReglaReserva> {
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+    if let Some(Some(dias)) = &cambios.dias_semana {
+        validar_dias(dias)?;
+    }
+
+    diesel::update(reglas_reserva::table.find(regla_id))
+        .set(&cambios)
+        .get_result::<ReglaReserva>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Regla de reserva {} no encontrada", regla_id)))
+}
+
+pub fn eliminar_regla(conn: &mut PgConnection, regla_id: i32) -> ApiResult<()> {
+    let borradas = diesel::delete(reglas_reserva::table.find(regla_id)).execute(conn)?;
+    if borradas == 0 {
+        return Err(ApiError::no_encontrado(format!("Regla de reserva {} no encontrada", regla_id)));
+    }
+    Ok(())
+}
+
+fn validar_dias(dias: &[i32]) -> ApiResult<()> {
+    if dias.is_empty() || dias.iter().any(|d| !(1..=7).contains(d)) {
+        return Err(ApiError::validacion("dias_semana debe contener valores de 1 (lunes) a 7 (domingo)")
+            .con_detalles(serde_json::json!({ "campo": "dias_semana" })));
+    }
+    Ok(())
+}
+
+// =============================
+// 🧮 Límites efectivos
+// =============================
+
+/// Combina las reglas que alcanzan a la cabaña en esa fecha: cada límite lo
+/// fija la regla más específica que lo define (cabaña + días > cabaña > días > general)
+pub fn limites(conn: &mut PgConnection, cabana_id: i32, fecha: NaiveDate) -> QueryResult<LimitesReserva> {
+    let dia = fecha.weekday().number_from_monday() as i32;
+
+    let mut reglas: Vec<ReglaReserva> = reglas_reserva::table
+        .filter(reglas_reserva::cabana_id.eq(cabana_id).or(reglas_reserva::cabana_id.is_null()))
+        .load::<ReglaReserva>(conn)?
+        .into_iter()
+        .filter(|r| r.dias_semana.as_ref().is_none_or(|d| d.contains(&dia)))
+        .collect();
+
+    // De menos a más específica: las últimas pisan a las primeras
+    reglas.sort_by_key(|r| {
+        let especificidad = 2 * r.cabana_id.is_some() as u8 + r.dias_semana.is_some() as u8;
+        (especificidad, r.id)
+    });
+
+    let mut limites = LimitesReserva::default();
+    for r in reglas {
+        limites.duracion_minima = r.duracion_minima.or(limites.duracion_minima);
+        limites.duracion_maxima = r.duracion_maxima.or(limites.duracion_maxima);
+        limites.granularidad = r.granularidad.or(limites.granularidad);
+        limites.anticipacion_minima = r.anticipacion_minima.or(limites.anticipacion_minima);
+        limites.anticipacion_maxima_dias = r.anticipacion_maxima_dias.or(limites.anticipacion_maxima_dias);
+    }
+    Ok(limites)
+}
+
+// =============================
+// ✅ Validación de una reserva
+//  - Se informan TODAS las reglas incumplidas, cada una con su campo,
+//    para que el formulario las muestre junto al input correspondiente
+//  - Anticipación y granularidad miran el inicio: no las controla quien
+//    llega sin reserva (se crea ya en curso), quien no mueve el inicio ni
+//    quien toma un horario ya retenido (oferta o bloqueo, que las pasaron
+//    al crearse)
+// =============================
+pub fn validar_reserva(
+    conn: &mut PgConnection,
+    cabana_id: i32,
+    fecha: NaiveDate,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+    controlar_anticipacion: bool,
+    controlar_granularidad: bool,
+) -> ApiResult<()> {
+    let limites = limites(conn, cabana_id, fecha)?;
+    let ahora = Local::now().naive_local();
+    let violaciones = evaluar(&limites, fecha, inicio, fin, ahora, controlar_anticipacion, controlar_granularidad);
+
+    if violaciones.is_empty() {
+        return Ok(());
+    }
+    Err(ApiError::validacion("La reserva no cumple las reglas de reserva")
+        .con_detalles(serde_json::json!({ "violaciones": violaciones })))
+}
+
+/// Reglas incumplidas por la reserva con los límites ya combinados
+fn evaluar(
+    limites: &LimitesReserva,
+    fecha: NaiveDate,
+    inicio: NaiveDateTime,
+    fin: NaiveDateTime,
+    ahora: NaiveDateTime,
+    controlar_anticipacion: bool,
+    controlar_granularidad: bool,
+) -> Vec<Violacion> {
+    let mut violaciones = Vec::new();
+    let duracion = (fin - inicio).num_minutes() as i32;
+
+    if let Some(minima) = limites.duracion_minima.filter(|m| duracion < *m) {
+        violaciones.push(Violacion {
+            campo: "hora_fin",
+            regla: "duracion_minima",
+            mensaje: format!("La reserva debe durar al menos {}", describir_minutos(minima)),
+            limite: minima,
+        });
+    }
+    if let Some(maxima) = limites.duracion_maxima.filter(|m| duracion > *m) {
+        violaciones.push(Violacion {
+            campo: "hora_fin",
+            regla: "duracion_maxima",
+            mensaje: format!("La reserva no puede durar más de {}", describir_minutos(maxima)),
+            limite: maxima,
+        });
+    }
+    if let Some(paso) = limites.granularidad.filter(|_| controlar_granularidad) {
+        let hora = inicio.time();
+        if hora.minute() as i32 % paso != 0 || hora.second() != 0 {
+            violaciones.push(Violacion {
+                campo: "hora_inicio",
+                regla: "granularidad",
+                mensaje: format!("La hora de inicio debe caer en múltiplos de {} minutos", paso),
+                limite: paso,
+            });
+        }
+    }
+
+    if controlar_anticipacion {
+        if let Some(minima) = limites.anticipacion_minima {
+            if inicio < ahora + Duration::minutes(minima as i64) {
+                violaciones.push(Violacion {
+                    campo: "hora_inicio",
+                    regla: "anticipacion_minima",
+                    mensaje: format!("Hay que reservar con al menos {} de anticipación", describir_minutos(minima)),
+                    limite: minima,
+                });
+            }
+        }
+        if let Some(dias) = limites.anticipacion_maxima_dias {
+            if fecha > ahora.date() + Duration::days(dias as i64) {
+                violaciones.push(Violacion {
+                    campo: "fecha_reserva",
+                    regla: "anticipacion_maxima_dias",
+                    mensaje: format!("Solo se puede reservar hasta {} días por adelantado", dias),
+                    limite: dias,
+                });
+            }
+        }
+    }
+
+    violaciones
+}
+
+/// 90 → "1 h 30 min", 120 → "2 h", 45 → "45 min"
+fn describir_minutos(minutos: i32) -> String {
+    match (minutos / 60, minutos % 60) {
+        (0, m) => format!("{} min", m),
+        (h, 0) => format!("{} h", h),
+        (h, m) => format!("{} h {} min", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Las reglas de la semilla: inicio en :00/:30, 1 h de anticipación,
+    /// hasta 60 días, entre 1 h y 6 h
+    fn limites() -> LimitesReserva {
+        LimitesReserva {
+            duracion_minima: Some(60),
+            duracion_maxima: Some(360),
+            granularidad: Some(30),
+            anticipacion_minima: Some(60),
+            anticipacion_maxima_dias: Some(60),
+        }
+    }
+
+    fn instante(hora: u32, minuto: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 23).unwrap().and_hms_opt(hora, minuto, 0).unwrap()
+    }
+
+    fn reglas(violaciones: &[Violacion]) -> Vec<&'static str> {
+        violaciones.iter().map(|v| v.regla).collect()
+    }
+
+    #[test]
+    fn una_reserva_nueva_controla_anticipacion_y_granularidad() {
+        let (inicio, fin) = (instante(20, 15), instante(22, 15));
+        let violaciones = evaluar(&limites(), inicio.date(), inicio, fin, instante(19, 45), true, true);
+        assert_eq!(reglas(&violaciones), vec!["granularidad", "anticipacion_minima"]);
+    }
+
+    #[test]
+    fn una_oferta_aceptada_tarde_no_pierde_el_horario() {
+        // Ofrecida a las 18:50 para las 20:00 (70 min, cumplía) y aceptada a
+        // las 19:05 (55 min): con el horario retenido no se vuelve a controlar
+        let (inicio, fin) = (instante(20, 0), instante(22, 0));
+        let al_ofrecer = evaluar(&limites(), inicio.date(), inicio, fin, instante(18, 50), true, true);
+        assert!(al_ofrecer.is_empty());
+
+        let al_aceptar = evaluar(&limites(), inicio.date(), inicio, fin, instante(19, 5), true, true);
+        assert_eq!(reglas(&al_aceptar), vec!["anticipacion_minima"]);
+        assert!(evaluar(&limites(), inicio.date(), inicio, fin, instante(19, 5), false, false).is_empty());
+    }
+
+    #[test]
+    fn la_duracion_se_controla_siempre() {
+        let (inicio, fin) = (instante(20, 7), instante(20, 37));
+        let violaciones = evaluar(&limites(), inicio.date(), inicio, fin, instante(20, 7), false, false);
+        assert_eq!(reglas(&violaciones), vec!["duracion_minima"]);
+    }
+
+    #[test]
+    fn anticipacion_maxima_en_dias() {
+        let ahora = instante(12, 0);
+        let fecha = ahora.date() + Duration::days(61);
+        let inicio = fecha.and_hms_opt(20, 0, 0).unwrap();
+        let violaciones = evaluar(&limites(), fecha, inicio, inicio + Duration::hours(2), ahora, true, true);
+        assert_eq!(reglas(&violaciones), vec!["anticipacion_maxima_dias"]);
+    }
+}
@@ -1,47 +1,323 @@
+use bigdecimal::BigDecimal;
+use chrono::{Local, NaiveDateTime};
 use diesel::prelude::*;
-use diesel::result::Error;
-//use chrono::{Local, NaiveDate, NaiveTime};
-use crate::models::{Reserva, NewReserva};
-use crate::schema::{reservas, cabanas};
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    EstadoReserva, FiltroReservas, OfertaEspera, Pagina, Reserva, NewReserva, UpdateReserva,
+};
+use crate::schema::reservas;
 
 // =============================
 // 📋 Listar reservas
+//  - Filtros por rango de fechas, cabaña, cliente, estados y texto
+//  - Paginación limit/offset con total de filas
 // =============================
-pub fn listar_reservas(conn: &mut PgConnection) -> QueryResult<Vec<Reserva>> {
-    reservas::table
-        .order((reservas::fecha_reserva.asc(), reservas::hora_inicio.asc()))
-        .load::<Reserva>(conn)
+const LIMITE_POR_DEFECTO: i64 = 100;
+const LIMITE_MAXIMO: i64 = 500;
+
+pub fn listar_reservas(conn: &mut PgConnection, filtro: FiltroReservas) -> ApiResult<Pagina<Reserva>> {
+    use crate::services::validaciones_service::parsear_fecha;
+
+    let desde = filtro.desde.as_deref().map(|v| parsear_fecha("desde", v)).transpose()?;
+    let hasta = filtro.hasta.as_deref().map(|v| parsear_fecha("hasta", v)).transpose()?;
+    let descendente = match filtro.orden.as_deref() {
+        None | Some("asc") => false,
+        Some("desc") => true,
+        Some(otro) => {
+            return Err(ApiError::validacion(format!("Orden '{}' no válido: use asc o desc", otro))
+                .con_detalles(serde_json::json!({ "campo": "orden" })))
+        }
+    };
+    let estados = filtro
+        .estado
+        .iter()
+        .map(|e| e.parse::<EstadoReserva>())
+        .collect::<ApiResult<Vec<_>>>()?;
+    let limit = filtro.limit.unwrap_or(LIMITE_POR_DEFECTO).clamp(1, LIMITE_MAXIMO);
+    let offset = filtro.offset.unwrap_or(0).max(0);
+    let texto = filtro.q.as_deref().map(|q| {
+        format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+    });
+
+    // Se construye dos veces (conteo y página) porque las consultas boxed no son clonables
+    let filtrar = || {
+        let mut query = reservas::table.into_boxed();
+        if let Some(d) = desde {
+            query = query.filter(reservas::fecha_reserva.ge(d));
+        }
+        if let Some(h) = hasta {
+            query = query.filter(reservas::fecha_reserva.le(h));
+        }
+        if let Some(c) = filtro.cabana_id {
+            query = query.filter(reservas::cabana_id.eq(c));
+        }
+        if let Some(c) = filtro.cliente_id {
+            query = query.filter(reservas::cliente_id.eq(c));
+        }
+        if let Some(s) = filtro.serie_id {
+            query = query.filter(reservas::serie_id.eq(s));
+        }
+        if !estados.is_empty() {
+            query = query.filter(reservas::estado.eq_any(estados.clone()));
+        }
+        if let Some(t) = texto.clone() {
+            query = query.filter(reservas::observaciones.ilike(t));
+        }
+        query
+    };
+
+    let total = filtrar().count().get_result::<i64>(conn)?;
+
+    let query = if descendente {
+        filtrar().order((reservas::inicio.desc(), reservas::id.desc()))
+    } else {
+        filtrar().order((reservas::inicio.asc(), reservas::id.asc()))
+    };
+
+    let items = query.limit(limit).offset(offset).load::<Reserva>(conn)?;
+
+    Ok(Pagina { items, total, limit, offset })
 }
 
 // =============================
 // ➕ Crear nueva reserva
+//  - `horario_retenido`: el horario viene de una oferta de la lista de espera
+//    o de un bloqueo, que ya pasaron anticipación y granularidad al tomarse;
+//    no se vuelven a controlar, así el plazo para aceptar no lo hace caer
 // =============================
-pub fn crear_reserva(conn: &mut PgConnection, nueva_reserva: NewReserva) -> QueryResult<Reserva> {
-    use crate::services::validaciones_service;
+pub fn crear_reserva(
+    conn: &mut PgConnection,
+    nueva_reserva: NewReserva,
+    horario_retenido: bool,
+) -> ApiResult<Reserva> {
+    use crate::services::{
+        cabanas_service, horarios_service, mantenimientos_service, precios_service, promociones_service,
+        reglas_reserva_service, validaciones_service,
+    };
 
-    conn.transaction::<Reserva, Error, _>(|conn| {
-        // Validar conflictos (solapamientos)
-        let hay_conflicto = validaciones_service::existe_conflicto(
-            conn,
-            nueva_reserva.cabana_id,
+    if !EstadoReserva::INICIALES.contains(&nueva_reserva.estado) {
+        return Err(ApiError::validacion(format!(
+            "Una reserva no puede crearse como '{}'",
+            nueva_reserva.estado
+        ))
+        .con_detalles(serde_json::json!({ "campo": "estado", "permitidos": EstadoReserva::INICIALES })));
+    }
+
+    conn.transaction::<Reserva, ApiError, _>(|conn| {
+        // Las cabañas archivadas no admiten nuevas reservas. El lock de la
+        // cabaña serializa esta alta con la de bloqueos y ofertas
+        let cabana = cabanas_service::bloquear_cabana_activa(conn, nueva_reserva.cabana_id)?;
+        validaciones_service::validar_personas(&cabana, nueva_reserva.num_personas)?;
+
+        validaciones_service::validar_horas(nueva_reserva.hora_inicio, nueva_reserva.hora_fin)?;
+
+        // Validar conflictos (solapamientos), incluso si la reserva cruza la medianoche.
+        // Si dos reservas pasan este chequeo a la vez, la restricción EXCLUDE
+        // `reservas_sin_solapes` rechaza la segunda con el mismo error 409.
+        let (inicio, fin) = validaciones_service::rango_reserva(
             nueva_reserva.fecha_reserva,
             nueva_reserva.hora_inicio,
             nueva_reserva.hora_fin,
-        )?;
+        );
 
-        if hay_conflicto {
-            return Err(Error::RollbackTransaction);
+        // Un cliente sin reserva previa se registra ya en curso: la reserva
+        // tiene que estar sucediendo ahora y el inicio es su hora de llegada
+        let presencial = nueva_reserva.estado == EstadoReserva::EnCurso;
+        let ahora = Local::now().naive_local();
+        if presencial && (ahora.date() < nueva_reserva.fecha_reserva || ahora >= fin) {
+            return Err(ApiError::validacion("Una reserva en curso tiene que incluir el momento actual")
+                .con_detalles(serde_json::json!({ "campo": "hora_inicio", "inicio": inicio, "fin": fin })));
         }
+        reglas_reserva_service::validar_reserva(
+            conn,
+            cabana.id,
+            nueva_reserva.fecha_reserva,
+            inicio,
+            fin,
+            controla_inicio(presencial, horario_retenido),
+            controla_inicio(presencial, horario_retenido),
+        )?;
+        horarios_service::validar_en_horario(conn, inicio, fin)?;
+        mantenimientos_service::validar_fuera_de_mantenimiento(conn, cabana.id, inicio, fin)?;
+
+        validaciones_service::validar_conflictos(
+            conn,
+            &cabana,
+            inicio,
+            fin,
+            None,
+            nueva_reserva.ignorar_limpieza,
+        )?;
+
+        // El precio se congela ahora: cambios posteriores de precio_hora no lo alteran
+        let monto = precios_service::monto_reserva(conn, &cabana, inicio, fin)?;
+
+        // El código se valida y se canjea en esta misma transacción
+        let promocion = match nueva_reserva.codigo_promocion.as_deref().filter(|c| !c.trim().is_empty()) {
+            Some(codigo) => {
+                let bruto = monto.as_ref().ok_or_else(|| {
+                    ApiError::validacion(format!(
+                        "La cabaña {} no tiene precio; no se puede aplicar un código de promoción",
+                        cabana.nombre
+                    ))
+                    .con_detalles(serde_json::json!({ "campo": "codigo_promocion" }))
+                })?;
+                let ctx = promociones_service::ContextoCanje {
+                    cabana_id: cabana.id,
+                    cliente_id: Some(nueva_reserva.cliente_id),
+                    fecha: nueva_reserva.fecha_reserva,
+                    hora_inicio: nueva_reserva.hora_inicio,
+                    inicio,
+                    fin,
+                    excluir_reserva: None,
+                };
+                Some(promociones_service::validar_codigo(conn, codigo, &ctx, bruto)?)
+            }
+            None => None,
+        };
+        let descuento = promocion.as_ref().map(|(_, d)| d.clone()).unwrap_or_default();
+        let monto = monto.map(|m| m - &descuento);
+
+        let llegada = presencial.then_some(ahora);
 
         // Insertar reserva
         let reserva = diesel::insert_into(reservas::table)
-            .values(&nueva_reserva)
+            .values((
+                &nueva_reserva,
+                reservas::monto_total.eq(monto),
+                reservas::descuento.eq(&descuento),
+                reservas::llegada.eq(llegada),
+            ))
             .get_result::<Reserva>(conn)?;
 
-        // Marcar cabaña como "ocupada" (estado lógico)
-        diesel::update(cabanas::table.find(reserva.cabana_id))
-            .set(cabanas::estado.eq("ocupada"))
-            .execute(conn)?;
+        if let Some((promo, descuento)) = promocion {
+            promociones_service::registrar_canje(conn, promo.id, reserva.id, reserva.cliente_id, descuento)?;
+        }
+
+        Ok(reserva)
+    })
+}
+
+/// Anticipación y granularidad solo se controlan en una reserva nueva de verdad:
+/// ni el cliente que ya llegó ni un horario retenido las vuelven a pasar
+fn controla_inicio(presencial: bool, horario_retenido: bool) -> bool {
+    !presencial && !horario_retenido
+}
+
+// =============================
+// ✏️ Modificar / reprogramar reserva
+//  - Todo en una transacción: si hay conflicto no se toca nada
+//  - El control de solapes ignora la propia reserva
+//  - Si cambian horario o cabaña se vuelve a cotizar con el precio vigente
+// =============================
+pub fn actualizar_reserva(
+    conn: &mut PgConnection,
+    reserva_id: i32,
+    cambios: UpdateReserva,
+) -> ApiResult<Reserva> {
+    use crate::services::{
+        cabanas_service, horarios_service, mantenimientos_service, precios_service, promociones_service,
+        reglas_reserva_service, validaciones_service,
+    };
+
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+
+    conn.transaction::<Reserva, ApiError, _>(|conn| {
+        let actual: Reserva = reservas::table
+            .find(reserva_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+        if actual.estado.es_final() {
+            return Err(ApiError::conflicto(format!(
+                "La reserva {} está {} y ya no puede modificarse",
+                actual.id, actual.estado
+            )));
+        }
+
+        let cabana = cambios.cabana_id.unwrap_or(actual.cabana_id);
+        let fecha = cambios.fecha_reserva.unwrap_or(actual.fecha_reserva);
+        let inicio = cambios.hora_inicio.unwrap_or(actual.hora_inicio);
+        let fin = cambios.hora_fin.unwrap_or(actual.hora_fin);
+
+        validaciones_service::validar_horas(inicio, fin)?;
+
+        let destino = if cabana != actual.cabana_id {
+            cabanas_service::bloquear_cabana_activa(conn, cabana)?
+        } else {
+            cabanas_service::bloquear_cabana(conn, cabana)?
+        };
+
+        // Cambiar de cabaña o de grupo obliga a revalidar capacidad
+        if cabana != actual.cabana_id || cambios.num_personas.is_some() {
+            validaciones_service::validar_personas(
+                &destino,
+                cambios.num_personas.unwrap_or(actual.num_personas),
+            )?;
+        }
+
+        let (desde, hasta) = validaciones_service::rango_reserva(fecha, inicio, fin);
+        if cabana != actual.cabana_id || desde != actual.inicio || hasta != actual.fin {
+            // Anticipación y granularidad solo cuentan si se mueve el inicio:
+            // cambiar de cabaña a último momento o alargar una reserva que
+            // empezó fuera de la grilla (cliente sin reserva) sigue permitido
+            let mueve_inicio = desde != actual.inicio;
+            reglas_reserva_service::validar_reserva(conn, cabana, fecha, desde, hasta, mueve_inicio, mueve_inicio)?;
+        }
+        if desde != actual.inicio || hasta != actual.fin {
+            horarios_service::validar_en_horario(conn, desde, hasta)?;
+        }
+
+        let cambia_horario = cabana != actual.cabana_id || desde != actual.inicio || hasta != actual.fin;
+
+        // Sin cambio de horario no hay nada que revisar (y una reserva que se
+        // forzó dentro del margen de limpieza sigue siendo editable)
+        if cambia_horario {
+            mantenimientos_service::validar_fuera_de_mantenimiento(conn, cabana, desde, hasta)?;
+            validaciones_service::validar_conflictos(
+                conn,
+                &destino,
+                desde,
+                hasta,
+                Some(actual.id),
+                cambios.ignorar_limpieza,
+            )?;
+        }
+
+        let reserva = if cambia_horario {
+            let monto = precios_service::monto_reserva(conn, &destino, desde, hasta)?;
+
+            // Un código canjeado se revalida contra el nuevo horario (no se
+            // puede reservar el martes con 2X1MARTES y pasarlo al sábado)
+            let mut descuento = BigDecimal::from(0);
+            if let (Some(codigo), Some(bruto)) = (promociones_service::codigo_canjeado(conn, actual.id)?, &monto) {
+                let ctx = promociones_service::ContextoCanje {
+                    cabana_id: cabana,
+                    cliente_id: Some(actual.cliente_id),
+                    fecha,
+                    hora_inicio: inicio,
+                    inicio: desde,
+                    fin: hasta,
+                    excluir_reserva: Some(actual.id),
+                };
+                let (promo, d) = promociones_service::validar_codigo(conn, &codigo, &ctx, bruto)?;
+                promociones_service::registrar_canje(conn, promo.id, actual.id, actual.cliente_id, d.clone())?;
+                descuento = d;
+            }
+            let monto = monto.map(|m| m - &descuento);
+
+            diesel::update(reservas::table.find(actual.id))
+                .set((&cambios, reservas::monto_total.eq(monto), reservas::descuento.eq(descuento)))
+                .get_result::<Reserva>(conn)?
+        } else {
+            diesel::update(reservas::table.find(actual.id))
+                .set(&cambios)
+                .get_result::<Reserva>(conn)?
+        };
 
         Ok(reserva)
     })
@@ -49,112 +325,197 @@ pub fn crear_reserva(conn: &mut PgConnection, nueva_reserva: NewReserva) -> Quer
 
 // =============================
 // ❌ Eliminar reserva
+//  - Si la reserva ocupaba la cabaña, el hueco se ofrece a la lista de espera
+//    y se devuelven las ofertas creadas
 // =============================
-pub fn eliminar_reserva(conn: &mut PgConnection, id: i32) -> QueryResult<usize> {
-    use crate::schema::cabanas::dsl::{cabanas, estado as estado_cabana};
+pub fn eliminar_reserva(conn: &mut PgConnection, id: i32) -> ApiResult<Vec<OfertaEspera>> {
+    use crate::schema::pagos;
+    use crate::services::lista_espera_service;
 
-    conn.transaction::<usize, Error, _>(|conn| {
-        // Obtener la reserva para liberar la cabaña luego
+    conn.transaction::<Vec<OfertaEspera>, ApiError, _>(|conn| {
         let reserva: Reserva = reservas::table.find(id).first(conn)?;
 
-        // Eliminar
-        let deleted = diesel::delete(reservas::table.find(id)).execute(conn)?;
+        // Los movimientos de caja no se pierden: con pagos solo se puede cancelar
+        let con_pagos = diesel::select(diesel::dsl::exists(pagos::table.filter(pagos::reserva_id.eq(id))))
+            .get_result::<bool>(conn)?;
+        if con_pagos {
+            return Err(ApiError::conflicto(format!(
+                "La reserva {} tiene pagos registrados; cancélala en lugar de eliminarla",
+                id
+            )));
+        }
 
-        // Liberar cabaña
-        diesel::update(cabanas.find(reserva.cabana_id))
-            .set(estado_cabana.eq("disponible"))
-            .execute(conn)?;
+        // Eliminar
+        diesel::delete(reservas::table.find(reserva.id)).execute(conn)?;
 
-        Ok(deleted)
+        if EstadoReserva::LIBERAN_CABANA.contains(&reserva.estado) {
+            return Ok(Vec::new());
+        }
+        lista_espera_service::ofrecer_hueco(conn, reserva.cabana_id, reserva.inicio, reserva.fin)
     })
 }
 
 // =============================
 // 🔄 Actualizar estado de reserva
+//  - Solo se permiten las transiciones de `EstadoReserva::transiciones`
+//  - 'en curso' es el check-in y 'completada' el check-out: se guarda la hora real
+//  - Un no_show queda contado en el perfil del cliente
+//  - Al cancelar, el hueco se ofrece a la lista de espera
 // =============================
 pub fn actualizar_estado_reserva(
     conn: &mut PgConnection,
     reserva_id: i32,
-    nuevo_estado: &str,
-) -> QueryResult<Reserva> {
-    use crate::schema::reservas::dsl::{reservas as t_reservas, estado as estado_reserva};
-    use crate::schema::cabanas::dsl::{cabanas as t_cabanas, estado as estado_cabana};
+    nuevo_estado: EstadoReserva,
+) -> ApiResult<(Reserva, Vec<OfertaEspera>)> {
+    use crate::schema::reservas::dsl::{reservas as t_reservas, estado as estado_reserva, llegada, salida};
+    use crate::services::{clientes_service, lista_espera_service};
+
+    conn.transaction::<(Reserva, Vec<OfertaEspera>), ApiError, _>(|conn| {
+        let actual: Reserva = t_reservas
+            .find(reserva_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| ApiError::no_encontrado(format!("Reserva {} no encontrada", reserva_id)))?;
+
+        // Repetir el mismo estado no es un error
+        if actual.estado == nuevo_estado {
+            return Ok((actual, Vec::new()));
+        }
+
+        validar_transicion(&actual, nuevo_estado)?;
+
+        let ahora = Local::now().naive_local();
+        if nuevo_estado == EstadoReserva::EnCurso {
+            validar_check_in(&actual, ahora)?;
+        }
+        let nueva_llegada = if nuevo_estado == EstadoReserva::EnCurso { Some(ahora) } else { actual.llegada };
+        let nueva_salida = if nuevo_estado == EstadoReserva::Completada { Some(ahora) } else { actual.salida };
 
-    conn.transaction::<Reserva, Error, _>(|conn| {
         let reserva_actualizada = diesel::update(t_reservas.find(reserva_id))
-            .set(estado_reserva.eq(nuevo_estado))
+            .set((estado_reserva.eq(nuevo_estado), llegada.eq(nueva_llegada), salida.eq(nueva_salida)))
             .get_result::<Reserva>(conn)?;
 
-        // Si se cancela o completa → liberar cabaña
-        if nuevo_estado == "cancelada" || nuevo_estado == "completada" {
-            diesel::update(t_cabanas.find(reserva_actualizada.cabana_id))
-                .set(estado_cabana.eq("disponible"))
-                .execute(conn)?;
+        if nuevo_estado == EstadoReserva::NoShow {
+            clientes_service::sumar_no_show(conn, reserva_actualizada.cliente_id)?;
         }
 
-        Ok(reserva_actualizada)
+        let ofertas = if nuevo_estado == EstadoReserva::Cancelada {
+            lista_espera_service::ofrecer_hueco(
+                conn,
+                reserva_actualizada.cabana_id,
+                reserva_actualizada.inicio,
+                reserva_actualizada.fin,
+            )?
+        } else {
+            Vec::new()
+        };
+
+        Ok((reserva_actualizada, ofertas))
     })
 }
 
+/// ❌ 409 con los estados alcanzables si la transición no está permitida
+fn validar_transicion(reserva: &Reserva, destino: EstadoReserva) -> ApiResult<()> {
+    if reserva.estado.puede_pasar_a(destino) {
+        return Ok(());
+    }
+    Err(ApiError::conflicto(format!(
+        "La reserva {} no puede pasar de '{}' a '{}'",
+        reserva.id, reserva.estado, destino
+    ))
+    .con_detalles(serde_json::json!({
+        "estado_actual": reserva.estado,
+        "permitidos": reserva.estado.transiciones(),
+    })))
+}
+
+/// 🛎️ El check-in se habilita el día de la reserva y hasta que termina
+fn validar_check_in(reserva: &Reserva, ahora: NaiveDateTime) -> ApiResult<()> {
+    let mensaje = if ahora.date() < reserva.fecha_reserva {
+        format!("El check-in de la reserva {} se habilita el {}", reserva.id, reserva.fecha_reserva)
+    } else if ahora >= reserva.fin {
+        format!("La reserva {} ya terminó; no se puede hacer el check-in", reserva.id)
+    } else {
+        return Ok(());
+    };
+    Err(ApiError::conflicto(mensaje)
+        .con_detalles(serde_json::json!({ "inicio": reserva.inicio, "fin": reserva.fin })))
+}
+
 // =============================
 // 🕒 Actualizar estados automáticos (opcional)
-//  - Marca "en curso" si ahora ∈ [inicio, fin)
-//  - Marca "completada" si ahora ≥ fin
-//  - Libera cabaña cuando corresponda
+//  - Usa los instantes reales, así que funciona con reservas que cruzan la medianoche
+//  - Nunca marca "en curso": eso lo hace el check-in
+//  - Marca "no_show" si pasó la tolerancia desde el inicio sin check-in;
+//    el no presentado se cuenta en el cliente y el hueco pasa a la lista de espera
+//  - Marca "completada" las que están en curso si ahora ≥ fin; sin check-out,
+//    la salida se registra a la hora de fin
+//  - No toca las cabañas: su ocupación se calcula al leer
+//  - Devuelve cuántas filas cambiaron y las ofertas que generaron los no_show
 // =============================
-pub fn actualizar_estados_automaticos(conn: &mut PgConnection) -> QueryResult<()> {
+pub fn actualizar_estados_automaticos(conn: &mut PgConnection) -> ApiResult<(usize, Vec<OfertaEspera>)> {
     use crate::schema::reservas::dsl::*;
-    use crate::schema::cabanas::dsl::{cabanas, estado as estado_cabana};
+    use crate::services::{clientes_service, configuracion_service, lista_espera_service};
 
-    use chrono::{Local, NaiveDate, NaiveTime};
-    let ahora = Local::now();
-    let fecha_actual: NaiveDate = ahora.date_naive();
-    let hora_actual: NaiveTime = ahora.time();
+    use chrono::Duration;
+    let tolerancia = Duration::minutes(configuracion_service::obtener(conn)?.minutos_tolerancia_no_show as i64);
+    let ahora: NaiveDateTime = Local::now().naive_local();
 
-    // Obtenemos TODAS las reservas del día actual (o incluso futuras si quieres ampliar)
-    let reservas_hoy = reservas
-        .filter(fecha_reserva.eq(fecha_actual))
-        .order((cabana_id.asc(), hora_inicio.asc()))
+    // Se eligen por estado y no por fecha: las que quedaron atrás (servidor
+    // caído, reservas de varios días) también se cierran
+    let vencidas = reservas
+        .filter(
+            estado
+                .eq_any([EstadoReserva::Pendiente, EstadoReserva::Confirmada])
+                .and(inicio.le(ahora - tolerancia))
+                .or(estado.eq(EstadoReserva::EnCurso).and(fin.le(ahora))),
+        )
+        .order((cabana_id.asc(), inicio.asc()))
         .load::<crate::models::Reserva>(conn)?;
 
-    // Vamos a llevar un registro de la última cabaña procesada
-    let mut ultima_cabana_id: Option<i32> = None;
-    let mut estado_cabana_actual = String::from("disponible");
+    let mut cambios = 0;
+    let mut ofertas = Vec::new();
 
-    for r in reservas_hoy {
-        // Si cambiamos de cabaña, reseteamos el estado
-        if Some(r.cabana_id) != ultima_cabana_id {
-            ultima_cabana_id = Some(r.cabana_id);
-            estado_cabana_actual = String::from("disponible");
-        }
+    for r in vencidas {
+        // Solo si sigue en el estado leído: un check-in o una cancelación
+        // que llegó mientras tanto gana, y el no presentado no se cuenta
+        let pendiente = reservas.find(r.id).filter(estado.eq(r.estado));
 
-        if r.estado != "cancelada" {
-            if hora_actual >= r.hora_inicio && hora_actual < r.hora_fin {
-                // En curso
-                diesel::update(reservas.find(r.id))
-                    .set(estado.eq("en curso"))
+        let (filas, nuevas) = conn.transaction::<_, ApiError, _>(|conn| {
+            if r.estado == EstadoReserva::EnCurso {
+                let filas = diesel::update(pendiente)
+                    .set((estado.eq(EstadoReserva::Completada), salida.eq(r.fin)))
                     .execute(conn)?;
+                return Ok((filas, Vec::new()));
+            }
 
-                estado_cabana_actual = "ocupada".to_string();
-            } else if hora_actual >= r.hora_fin {
-                // Completada
-                diesel::update(reservas.find(r.id))
-                    .set(estado.eq("completada"))
-                    .execute(conn)?;
-            } else if hora_actual < r.hora_inicio {
-                // Próxima reserva
-                diesel::update(reservas.find(r.id))
-                    .set(estado.eq("pendiente"))
-                    .execute(conn)?;
+            let filas = diesel::update(pendiente).set(estado.eq(EstadoReserva::NoShow)).execute(conn)?;
+            if filas == 0 {
+                return Ok((0, Vec::new()));
             }
-        }
+            clientes_service::sumar_no_show(conn, r.cliente_id)?;
+            let nuevas = lista_espera_service::ofrecer_hueco(conn, r.cabana_id, r.inicio, r.fin)?;
+            Ok((filas, nuevas))
+        })?;
 
-        // Al final de cada grupo de cabaña, reflejamos el estado actual
-        diesel::update(cabanas.find(r.cabana_id))
-            .set(estado_cabana.eq(&estado_cabana_actual))
-            .execute(conn)?;
+        cambios += filas;
+        ofertas.extend(nuevas);
     }
 
-    Ok(())
+    Ok((cambios, ofertas))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solo_una_reserva_nueva_controla_el_inicio() {
+        assert!(controla_inicio(false, false));
+        // Cliente sin reserva que ya llegó
+        assert!(!controla_inicio(true, false));
+        // Oferta de la lista de espera o bloqueo confirmado
+        assert!(!controla_inicio(false, true));
+    }
+}
@@ -0,0 +1,465 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use diesel::prelude::*;
+use crate::errors::{ApiError, ApiResult};
+use crate::models::{
+    AlcanceSerie, CambiosSerie, DetalleSerie, EstadoReserva, FrecuenciaSerie, NewReserva, NewSerieReserva,
+    OcurrenciaRechazada, OfertaEspera, Reserva, SerieReserva, UpdateReserva,
+};
+use crate::schema::{reservas, series_reserva};
+
+/// Tope de ocurrencias por serie (dos años de una reserva semanal)
+pub const MAX_OCURRENCIAS: usize = 104;
+
+// =============================
+// 🔁 Crear una serie
+//  - Cada fecha pasa por `crear_reserva`, con todas sus validaciones
+//  - Las fechas que fallen se informan juntas; con `omitir_conflictos`
+//    la serie se crea sin ellas, si no, no se crea nada
+// =============================
+pub fn crear_serie(conn: &mut PgConnection, nueva: NewSerieReserva) -> ApiResult<DetalleSerie> {
+    use crate::services::{cabanas_service, reservas_service, validaciones_service};
+
+    let fechas = fechas_de_patron(&nueva)?;
+
+    conn.transaction::<DetalleSerie, ApiError, _>(|conn| {
+        // Lo que falla igual para todas las fechas se informa una sola vez
+        let cabana = cabanas_service::obtener_cabana_activa(conn, nueva.cabana_id)?;
+        validaciones_service::validar_personas(&cabana, nueva.num_personas)?;
+
+        let serie = diesel::insert_into(series_reserva::table)
+            .values(&nueva)
+            .get_result::<SerieReserva>(conn)?;
+
+        let mut creadas = Vec::new();
+        let mut rechazadas = Vec::new();
+        for fecha in fechas {
+            let ocurrencia = NewReserva {
+                cliente_id: serie.cliente_id,
+                cabana_id: serie.cabana_id,
+                fecha_reserva: fecha,
+                hora_inicio: serie.hora_inicio,
+                hora_fin: serie.hora_fin,
+                estado: EstadoReserva::default(),
+                observaciones: serie.observaciones.clone(),
+                num_personas: serie.num_personas,
+                codigo_promocion: None,
+                ignorar_limpieza: nueva.ignorar_limpieza,
+                serie_id: Some(serie.id),
+            };
+            // `crear_reserva` abre un savepoint: si la fecha falla, solo se
+            // deshace esa ocurrencia
+            match reservas_service::crear_reserva(conn, ocurrencia, false) {
+                Ok(reserva) => creadas.push(reserva),
+                Err(e) if e.es_rechazo() => rechazadas.push(OcurrenciaRechazada { fecha, error: e.into_body() }),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if creadas.is_empty() {
+            return Err(ApiError::conflicto("Ninguna fecha de la serie se puede reservar")
+                .con_detalles(serde_json::json!({ "rechazadas": rechazadas })));
+        }
+        if !rechazadas.is_empty() && !nueva.omitir_conflictos {
+            return Err(ApiError::conflicto(format!(
+                "{} de {} fechas de la serie no se pueden reservar",
+                rechazadas.len(),
+                rechazadas.len() + creadas.len()
+            ))
+            .con_detalles(serde_json::json!({
+                "rechazadas": rechazadas,
+                "forzar_con": "omitir_conflictos",
+            })));
+        }
+
+        Ok(DetalleSerie { serie, reservas: creadas, omitidas: rechazadas })
+    })
+}
+
+/// Fechas del patrón, en orden. Falla si el patrón está incompleto o genera
+/// más de `MAX_OCURRENCIAS` fechas.
+fn fechas_de_patron(patron: &NewSerieReserva) -> ApiResult<Vec<NaiveDate>> {
+    if patron.hasta.is_some() == patron.repeticiones.is_some() {
+        return Err(ApiError::validacion("La serie termina en una fecha (hasta) o tras N repeticiones, no ambas")
+            .con_detalles(serde_json::json!({ "campo": "hasta" })));
+    }
+    if patron.intervalo < 1 {
+        return Err(ApiError::validacion("El intervalo debe ser al menos 1")
+            .con_detalles(serde_json::json!({ "campo": "intervalo" })));
+    }
+    if matches!(patron.repeticiones, Some(n) if n < 1) {
+        return Err(ApiError::validacion("La serie debe tener al menos una repetición")
+            .con_detalles(serde_json::json!({ "campo": "repeticiones" })));
+    }
+    if matches!(patron.hasta, Some(h) if h < patron.fecha_inicio) {
+        return Err(ApiError::validacion("La fecha de fin de la serie es anterior a la de inicio")
+            .con_detalles(serde_json::json!({ "campo": "hasta" })));
+    }
+
+    let demasiadas = || {
+        ApiError::validacion(format!("La serie no puede tener más de {} fechas", MAX_OCURRENCIAS))
+            .con_detalles(serde_json::json!({ "campo": "repeticiones", "maximo": MAX_OCURRENCIAS }))
+    };
+    let limite = match patron.repeticiones {
+        Some(n) if n as usize > MAX_OCURRENCIAS => return Err(demasiadas()),
+        Some(n) => n as usize,
+        None => MAX_OCURRENCIAS + 1,
+    };
+
+    let mut fechas = Vec::new();
+    let mut paso: i64 = 0;
+    while fechas.len() < limite {
+        let candidata = match patron.frecuencia {
+            FrecuenciaSerie::Semanal => Some(patron.fecha_inicio + Duration::weeks(paso * patron.intervalo as i64)),
+            FrecuenciaSerie::Mensual => mismo_dia_del_mes(patron.fecha_inicio, paso * patron.intervalo as i64),
+        };
+        paso += 1;
+
+        // Un 31 no existe en todos los meses: ese mes se saltea
+        let Some(fecha) = candidata else { continue };
+        if matches!(patron.hasta, Some(h) if fecha > h) {
+            break;
+        }
+        fechas.push(fecha);
+    }
+
+    if fechas.len() > MAX_OCURRENCIAS {
+        return Err(demasiadas());
+    }
+    Ok(fechas)
+}
+
+/// `inicio` desplazada `meses` meses conservando el día; `None` si ese mes no lo tiene
+fn mismo_dia_del_mes(inicio: NaiveDate, meses: i64) -> Option<NaiveDate> {
+    let total = inicio.year() as i64 * 12 + inicio.month0() as i64 + meses;
+    NaiveDate::from_ymd_opt((total / 12) as i32, (total % 12) as u32 + 1, inicio.day())
+}
+
+// =============================
+// 🔎 Consultar una serie
+// =============================
+pub fn obtener_serie(conn: &mut PgConnection, serie_id: i32) -> ApiResult<DetalleSerie> {
+    let serie = buscar(conn, serie_id)?;
+    let reservas = reservas::table
+        .filter(reservas::serie_id.eq(serie.id))
+        .order(reservas::inicio.asc())
+        .load::<Reserva>(conn)?;
+
+    Ok(DetalleSerie { serie, reservas, omitidas: Vec::new() })
+}
+
+fn buscar(conn: &mut PgConnection, serie_id: i32) -> ApiResult<SerieReserva> {
+    series_reserva::table
+        .find(serie_id)
+        .first::<SerieReserva>(conn)
+        .optional()?
+        .ok_or_else(|| ApiError::no_encontrado(format!("Serie {} no encontrada", serie_id)))
+}
+
+// =============================
+// 🎯 Alcance de ediciones y cancelaciones
+//  - `una`: la ocurrencia indicada, tal cual
+//  - `siguientes` / `todas`: solo las que todavía no empezaron ni
+//    terminaron (las pasadas quedan como historial)
+// =============================
+enum Alcance {
+    Una(i32),
+    Siguientes(i32),
+    Todas,
+}
+
+fn parsear_alcance(alcance: &AlcanceSerie) -> ApiResult<Alcance> {
+    let reserva_id = || {
+        alcance.reserva_id.ok_or_else(|| {
+            ApiError::validacion("Indica la ocurrencia con reserva_id")
+                .con_detalles(serde_json::json!({ "campo": "reserva_id" }))
+        })
+    };
+    match alcance.alcance.as_deref().unwrap_or("todas") {
+        "una" => Ok(Alcance::Una(reserva_id()?)),
+        "siguientes" => Ok(Alcance::Siguientes(reserva_id()?)),
+        "todas" => Ok(Alcance::Todas),
+        otro => Err(ApiError::validacion(format!("Alcance '{}' no válido", otro)).con_detalles(
+            serde_json::json!({ "campo": "alcance", "validos": ["una", "siguientes", "todas"] }),
+        )),
+    }
+}
+
+fn ocurrencias(conn: &mut PgConnection, serie: &SerieReserva, alcance: &Alcance) -> ApiResult<Vec<Reserva>> {
+    let de_la_serie = |conn: &mut PgConnection, reserva_id: i32| {
+        reservas::table
+            .filter(reservas::id.eq(reserva_id))
+            .filter(reservas::serie_id.eq(serie.id))
+            .for_update()
+            .first::<Reserva>(conn)
+            .optional()?
+            .ok_or_else(|| {
+                ApiError::no_encontrado(format!("La reserva {} no pertenece a la serie {}", reserva_id, serie.id))
+            })
+    };
+
+    let ahora = Local::now().naive_local();
+    let desde = match alcance {
+        Alcance::Una(id) => return Ok(vec![de_la_serie(conn, *id)?]),
+        Alcance::Siguientes(id) => de_la_serie(conn, *id)?.inicio.max(ahora),
+        Alcance::Todas => ahora,
+    };
+
+    Ok(reservas::table
+        .filter(reservas::serie_id.eq(serie.id))
+        .filter(reservas::estado.ne_all(EstadoReserva::FINALES))
+        .filter(reservas::inicio.ge(desde))
+        .order(reservas::inicio.asc())
+        .for_update()
+        .load::<Reserva>(conn)?)
+}
+
+// =============================
+// ✏️ Editar ocurrencias
+//  - Cada una pasa por `actualizar_reserva`; si alguna no se puede mover
+//    no se toca ninguna y se informan todas las fechas que fallaron
+// =============================
+pub fn actualizar_serie(
+    conn: &mut PgConnection,
+    serie_id: i32,
+    alcance: AlcanceSerie,
+    cambios: CambiosSerie,
+) -> ApiResult<DetalleSerie> {
+    use crate::services::reservas_service;
+
+    if cambios.esta_vacio() {
+        return Err(ApiError::validacion("No se envió ningún campo para actualizar"));
+    }
+    let alcance = parsear_alcance(&alcance)?;
+
+    conn.transaction::<DetalleSerie, ApiError, _>(|conn| {
+        let serie = buscar(conn, serie_id)?;
+        let objetivo = ocurrencias(conn, &serie, &alcance)?;
+        if objetivo.is_empty() {
+            return Err(ApiError::conflicto(format!("La serie {} no tiene ocurrencias pendientes", serie.id)));
+        }
+
+        let mut rechazadas = Vec::new();
+        for r in &objetivo {
+            let cambios_reserva = UpdateReserva {
+                cabana_id: cambios.cabana_id,
+                fecha_reserva: None,
+                hora_inicio: cambios.hora_inicio,
+                hora_fin: cambios.hora_fin,
+                observaciones: cambios.observaciones.clone().map(Some),
+                num_personas: cambios.num_personas,
+                ignorar_limpieza: cambios.ignorar_limpieza,
+            };
+            match reservas_service::actualizar_reserva(conn, r.id, cambios_reserva) {
+                Ok(_) => {}
+                Err(e) if e.es_rechazo() => {
+                    rechazadas.push(OcurrenciaRechazada { fecha: r.fecha_reserva, error: e.into_body() })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !rechazadas.is_empty() {
+            return Err(ApiError::conflicto(format!(
+                "{} de {} ocurrencias no admiten el cambio; no se modificó ninguna",
+                rechazadas.len(),
+                objetivo.len()
+            ))
+            .con_detalles(serde_json::json!({ "rechazadas": rechazadas })));
+        }
+
+        // El patrón solo se reescribe cuando el cambio alcanza a toda la serie
+        if matches!(alcance, Alcance::Todas) {
+            diesel::update(series_reserva::table.find(serie.id))
+                .set(&cambios)
+                .execute(conn)?;
+        }
+
+        obtener_serie(conn, serie.id)
+    })
+}
+
+// =============================
+// ❌ Cancelar ocurrencias
+// =============================
+/// Devuelve también las ofertas de lista de espera que generaron los huecos
+pub fn cancelar_serie(
+    conn: &mut PgConnection,
+    serie_id: i32,
+    alcance: AlcanceSerie,
+) -> ApiResult<(DetalleSerie, Vec<OfertaEspera>)> {
+    use crate::services::reservas_service;
+
+    let alcance = parsear_alcance(&alcance)?;
+
+    conn.transaction::<(DetalleSerie, Vec<OfertaEspera>), ApiError, _>(|conn| {
+        let serie = buscar(conn, serie_id)?;
+        let objetivo = ocurrencias(conn, &serie, &alcance)?;
+        if objetivo.is_empty() {
+            return Err(ApiError::conflicto(format!("La serie {} no tiene ocurrencias pendientes", serie.id)));
+        }
+
+        let mut ofertas = Vec::new();
+        for r in objetivo {
+            let (_, nuevas) = reservas_service::actualizar_estado_reserva(conn, r.id, EstadoReserva::Cancelada)?;
+            ofertas.extend(nuevas);
+        }
+
+        Ok((obtener_serie(conn, serie.id)?, ofertas))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Weekday};
+
+    fn fecha(anio: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(anio, mes, dia).unwrap()
+    }
+
+    fn patron(frecuencia: FrecuenciaSerie, fecha_inicio: NaiveDate) -> NewSerieReserva {
+        NewSerieReserva {
+            cliente_id: 1,
+            cabana_id: 1,
+            frecuencia,
+            intervalo: 1,
+            fecha_inicio,
+            hasta: None,
+            repeticiones: None,
+            hora_inicio: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            hora_fin: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            num_personas: 2,
+            observaciones: None,
+            omitir_conflictos: false,
+            ignorar_limpieza: false,
+        }
+    }
+
+    fn campo_del_error(patron: &NewSerieReserva) -> serde_json::Value {
+        let error = fechas_de_patron(patron).unwrap_err();
+        assert_eq!(error.status(), rocket::http::Status::UnprocessableEntity);
+        error.into_body().details.unwrap()["campo"].clone()
+    }
+
+    #[test]
+    fn mismo_dia_del_mes_saltea_los_meses_sin_ese_dia() {
+        let inicio = fecha(2026, 1, 31);
+        assert_eq!(mismo_dia_del_mes(inicio, 0), Some(inicio));
+        assert_eq!(mismo_dia_del_mes(inicio, 1), None);
+        assert_eq!(mismo_dia_del_mes(inicio, 2), Some(fecha(2026, 3, 31)));
+        assert_eq!(mismo_dia_del_mes(inicio, 3), None);
+        assert_eq!(mismo_dia_del_mes(inicio, 11), Some(fecha(2026, 12, 31)));
+        assert_eq!(mismo_dia_del_mes(inicio, 12), Some(fecha(2027, 1, 31)));
+    }
+
+    #[test]
+    fn mismo_dia_del_mes_respeta_los_bisiestos() {
+        assert_eq!(mismo_dia_del_mes(fecha(2027, 1, 29), 1), None);
+        assert_eq!(mismo_dia_del_mes(fecha(2028, 1, 29), 1), Some(fecha(2028, 2, 29)));
+    }
+
+    #[test]
+    fn mensual_el_31_cuenta_solo_los_meses_que_lo_tienen() {
+        let mut p = patron(FrecuenciaSerie::Mensual, fecha(2026, 1, 31));
+        p.repeticiones = Some(4);
+        assert_eq!(
+            fechas_de_patron(&p).unwrap(),
+            vec![fecha(2026, 1, 31), fecha(2026, 3, 31), fecha(2026, 5, 31), fecha(2026, 7, 31)]
+        );
+
+        p.repeticiones = None;
+        p.hasta = Some(fecha(2026, 6, 30));
+        assert_eq!(
+            fechas_de_patron(&p).unwrap(),
+            vec![fecha(2026, 1, 31), fecha(2026, 3, 31), fecha(2026, 5, 31)]
+        );
+    }
+
+    #[test]
+    fn hasta_y_repeticiones_son_excluyentes() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 10, 23));
+        assert_eq!(campo_del_error(&p), "hasta");
+
+        p.hasta = Some(fecha(2026, 11, 30));
+        p.repeticiones = Some(3);
+        assert_eq!(campo_del_error(&p), "hasta");
+
+        p.repeticiones = None;
+        assert!(fechas_de_patron(&p).is_ok());
+        p.hasta = None;
+        p.repeticiones = Some(3);
+        assert!(fechas_de_patron(&p).is_ok());
+    }
+
+    #[test]
+    fn hasta_es_inclusivo() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 10, 23));
+        p.hasta = Some(fecha(2026, 11, 6));
+        assert_eq!(
+            fechas_de_patron(&p).unwrap(),
+            vec![fecha(2026, 10, 23), fecha(2026, 10, 30), fecha(2026, 11, 6)]
+        );
+    }
+
+    #[test]
+    fn rechaza_patrones_invalidos() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 10, 23));
+        p.repeticiones = Some(0);
+        assert_eq!(campo_del_error(&p), "repeticiones");
+
+        p.repeticiones = Some(3);
+        p.intervalo = 0;
+        assert_eq!(campo_del_error(&p), "intervalo");
+
+        p.intervalo = 1;
+        p.repeticiones = None;
+        p.hasta = Some(fecha(2026, 10, 22));
+        assert_eq!(campo_del_error(&p), "hasta");
+    }
+
+    #[test]
+    fn tope_de_ocurrencias() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 1, 1));
+        p.repeticiones = Some(MAX_OCURRENCIAS as i32);
+        assert_eq!(fechas_de_patron(&p).unwrap().len(), MAX_OCURRENCIAS);
+
+        p.repeticiones = Some(MAX_OCURRENCIAS as i32 + 1);
+        assert_eq!(campo_del_error(&p), "repeticiones");
+
+        // Con `hasta`: 104 semanas caben justo, una más no
+        p.repeticiones = None;
+        p.hasta = Some(fecha(2026, 1, 1) + Duration::weeks(MAX_OCURRENCIAS as i64 - 1));
+        assert_eq!(fechas_de_patron(&p).unwrap().len(), MAX_OCURRENCIAS);
+        p.hasta = Some(fecha(2026, 1, 1) + Duration::weeks(MAX_OCURRENCIAS as i64));
+        assert_eq!(campo_del_error(&p), "repeticiones");
+    }
+
+    #[test]
+    fn intervalo_mayor_a_uno() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 10, 23));
+        p.intervalo = 2;
+        p.repeticiones = Some(3);
+        assert_eq!(
+            fechas_de_patron(&p).unwrap(),
+            vec![fecha(2026, 10, 23), fecha(2026, 11, 6), fecha(2026, 11, 20)]
+        );
+
+        let mut p = patron(FrecuenciaSerie::Mensual, fecha(2026, 10, 31));
+        p.intervalo = 3;
+        p.repeticiones = Some(3);
+        // Enero tiene 31, abril no, julio sí
+        assert_eq!(
+            fechas_de_patron(&p).unwrap(),
+            vec![fecha(2026, 10, 31), fecha(2027, 1, 31), fecha(2027, 7, 31)]
+        );
+    }
+
+    #[test]
+    fn semanal_cruza_el_fin_de_anio_conservando_el_dia() {
+        let mut p = patron(FrecuenciaSerie::Semanal, fecha(2026, 12, 17));
+        p.repeticiones = Some(4);
+        let fechas = fechas_de_patron(&p).unwrap();
+
+        assert_eq!(fechas, vec![fecha(2026, 12, 17), fecha(2026, 12, 24), fecha(2026, 12, 31), fecha(2027, 1, 7)]);
+        assert!(fechas.iter().all(|f| f.weekday() == Weekday::Thu));
+    }
+}
@@ -1,29 +1,213 @@
 use diesel::prelude::*;
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::errors::{ApiError, ApiResult, MENSAJE_CONFLICTO_HORARIO};
+use crate::models::{Cabana, EstadoReserva};
+use crate::services::configuracion_service;
 //use crate::schema::reservas;
 
-/// Retorna `true` si existe solapamiento para (cabana_id, fecha, [inicio, fin))
-pub fn existe_conflicto(
-    conn: &mut PgConnection,
-    cabana: i32,
+/// Convierte un parámetro de consulta `YYYY-MM-DD` en fecha
+pub fn parsear_fecha(campo: &str, valor: &str) -> ApiResult<NaiveDate> {
+    NaiveDate::parse_from_str(valor, "%Y-%m-%d").map_err(|_| {
+        ApiError::validacion(format!("Fecha inválida en '{}': se espera YYYY-MM-DD", campo))
+            .con_detalles(serde_json::json!({ "campo": campo, "valor": valor }))
+    })
+}
+
+/// Convierte un parámetro de consulta `HH:MM` (o `HH:MM:SS`) en hora
+pub fn parsear_hora(campo: &str, valor: &str) -> ApiResult<NaiveTime> {
+    NaiveTime::parse_from_str(valor, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(valor, "%H:%M"))
+        .map_err(|_| {
+            ApiError::validacion(format!("Hora inválida en '{}': se espera HH:MM", campo))
+                .con_detalles(serde_json::json!({ "campo": campo, "valor": valor }))
+        })
+}
+
+/// 👥 El grupo debe caber en la cabaña y cubrir su ocupación mínima
+pub fn validar_personas(cabana: &Cabana, num_personas: i32) -> ApiResult<()> {
+    let mensaje = if num_personas < 1 {
+        "La reserva debe ser para al menos 1 persona".to_string()
+    } else if num_personas > cabana.capacidad {
+        format!(
+            "La cabaña {} admite hasta {} personas (se pidieron {})",
+            cabana.nombre, cabana.capacidad, num_personas
+        )
+    } else if num_personas < cabana.ocupacion_minima {
+        format!(
+            "La cabaña {} requiere al menos {} personas (se pidieron {})",
+            cabana.nombre, cabana.ocupacion_minima, num_personas
+        )
+    } else {
+        return Ok(());
+    };
+
+    Err(ApiError::validacion(mensaje).con_detalles(serde_json::json!({
+        "campo": "num_personas",
+        "capacidad": cabana.capacidad,
+        "ocupacion_minima": cabana.ocupacion_minima,
+    })))
+}
+
+/// Misma hora de inicio y de fin no es "24 horas": se rechaza antes de
+/// llegar a `rango_reserva` (la base tiene el mismo CHECK)
+pub fn validar_horas(hora_inicio: NaiveTime, hora_fin: NaiveTime) -> ApiResult<()> {
+    if hora_fin == hora_inicio {
+        return Err(ApiError::validacion("La hora de fin debe ser distinta de la de inicio")
+            .con_detalles(serde_json::json!({ "campo": "hora_fin" })));
+    }
+    Ok(())
+}
+
+/// Instantes `[inicio, fin)` de una reserva. Si `hora_fin <= hora_inicio`,
+/// la reserva cruza la medianoche y termina al día siguiente
+/// (misma regla que las columnas generadas `reservas.inicio` / `reservas.fin`).
+pub fn rango_reserva(
     fecha: NaiveDate,
-    inicio_nuevo: NaiveTime,
-    fin_nuevo: NaiveTime,
-) -> QueryResult<bool> {
+    hora_inicio: NaiveTime,
+    hora_fin: NaiveTime,
+) -> (NaiveDateTime, NaiveDateTime) {
+    let inicio = fecha.and_time(hora_inicio);
+    let fin = if hora_fin <= hora_inicio {
+        (fecha + Duration::days(1)).and_time(hora_fin)
+    } else {
+        fecha.and_time(hora_fin)
+    };
+    (inicio, fin)
+}
+
+/// Margen de limpieza efectivo de la cabaña (el propio o el global)
+pub fn minutos_limpieza(conn: &mut PgConnection, cabana: &Cabana) -> QueryResult<i64> {
+    match cabana.minutos_limpieza {
+        Some(m) => Ok(m as i64),
+        None => Ok(configuracion_service::obtener(conn)?.minutos_limpieza as i64),
+    }
+}
+
+/// ⚠️ Controla que `[inicio, fin)` no choque con otra reserva activa ni con un
+/// bloqueo temporal vigente de la cabaña, ni invada su margen de limpieza
+/// (antes y después).
+///
+/// - Solape real → 409 `conflicto_horario` con `solo_limpieza: false`
+/// - Solo el margen → 409 con `solo_limpieza: true`, salvo que el encargado
+///   lo fuerce con `ignorar_limpieza`
+///
+/// `excluir_reserva` permite ignorar una reserva concreta, p. ej. la que se
+/// está reprogramando, para que no choque consigo misma.
+pub fn validar_conflictos(
+    conn: &mut PgConnection,
+    cabana: &Cabana,
+    inicio_nuevo: NaiveDateTime,
+    fin_nuevo: NaiveDateTime,
+    excluir_reserva: Option<i32>,
+    ignorar_limpieza: bool,
+) -> ApiResult<()> {
+    use crate::schema::bloqueos;
     use crate::schema::reservas::dsl::{
-        reservas as t_reservas, cabana_id, fecha_reserva, hora_inicio, hora_fin, estado,
+        reservas as t_reservas, id, cabana_id, inicio, fin, estado,
     };
 
-    // Regla de solape: (inicio < fin_nuevo) AND (fin > inicio_nuevo)
-    // Además, ignoramos reservas canceladas
-    let count = t_reservas
-        .filter(cabana_id.eq(cabana))
-        .filter(fecha_reserva.eq(fecha))
-        .filter(estado.ne("cancelada"))
-        .filter(hora_inicio.lt(fin_nuevo))
-        .filter(hora_fin.gt(inicio_nuevo))
-        .count()
-        .get_result::<i64>(conn)?;
-
-    Ok(count > 0)
+    let margen = Duration::minutes(minutos_limpieza(conn, cabana)?);
+
+    // Regla de solape ampliada con el margen:
+    // (inicio < fin_nuevo + margen) AND (fin + margen > inicio_nuevo)
+    // Además, ignoramos reservas canceladas o no presentadas
+    let mut query = t_reservas
+        .filter(cabana_id.eq(cabana.id))
+        .filter(estado.ne_all(EstadoReserva::LIBERAN_CABANA))
+        .filter(inicio.lt(fin_nuevo + margen))
+        .filter(fin.gt(inicio_nuevo - margen))
+        .select((id, inicio, fin))
+        .into_boxed();
+
+    if let Some(reserva_id) = excluir_reserva {
+        query = query.filter(id.ne(reserva_id));
+    }
+
+    let vecinas = query.load::<(i32, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    // Los bloqueos vigentes ocupan la cabaña igual que una reserva
+    let bloqueos_vecinos = bloqueos::table
+        .filter(bloqueos::cabana_id.eq(cabana.id))
+        .filter(bloqueos::vence.gt(chrono::Local::now().naive_local()))
+        .filter(bloqueos::inicio.lt(fin_nuevo + margen))
+        .filter(bloqueos::fin.gt(inicio_nuevo - margen))
+        .select((bloqueos::id, bloqueos::inicio, bloqueos::fin))
+        .load::<(i32, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    let solapadas: Vec<i32> = vecinas
+        .iter()
+        .filter(|(_, i, f)| *i < fin_nuevo && *f > inicio_nuevo)
+        .map(|(r, _, _)| *r)
+        .collect();
+    if !solapadas.is_empty() {
+        return Err(ApiError::conflicto_horario(MENSAJE_CONFLICTO_HORARIO)
+            .con_detalles(serde_json::json!({ "solo_limpieza": false, "reservas": solapadas })));
+    }
+
+    let bloqueados: Vec<i32> = bloqueos_vecinos
+        .iter()
+        .filter(|(_, i, f)| *i < fin_nuevo && *f > inicio_nuevo)
+        .map(|(b, _, _)| *b)
+        .collect();
+    if !bloqueados.is_empty() {
+        return Err(ApiError::conflicto_horario(
+            "⚠️ El horario está bloqueado temporalmente (por otro anfitrión o por una oferta de la lista de espera).",
+        )
+        .con_detalles(serde_json::json!({ "solo_limpieza": false, "reservas": [], "bloqueos": bloqueados })));
+    }
+
+    if (!vecinas.is_empty() || !bloqueos_vecinos.is_empty()) && !ignorar_limpieza {
+        let ids: Vec<i32> = vecinas.iter().map(|(r, _, _)| *r).collect();
+        let ids_bloqueos: Vec<i32> = bloqueos_vecinos.iter().map(|(b, _, _)| *b).collect();
+        return Err(ApiError::conflicto_horario(format!(
+            "⚠️ El horario no deja los {} minutos de limpieza con otra reserva de la cabaña.",
+            margen.num_minutes()
+        ))
+        .con_detalles(serde_json::json!({
+            "solo_limpieza": true,
+            "minutos_limpieza": margen.num_minutes(),
+            "reservas": ids,
+            "bloqueos": ids_bloqueos,
+            "forzar_con": "ignorar_limpieza",
+        })));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hora(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn rango_dentro_del_mismo_dia() {
+        let fecha = NaiveDate::from_ymd_opt(2026, 10, 23).unwrap();
+        let (inicio, fin) = rango_reserva(fecha, hora(19, 0), hora(21, 30));
+        assert_eq!(inicio, fecha.and_time(hora(19, 0)));
+        assert_eq!(fin, fecha.and_time(hora(21, 30)));
+    }
+
+    #[test]
+    fn rango_que_cruza_la_medianoche_termina_al_dia_siguiente() {
+        let fecha = NaiveDate::from_ymd_opt(2026, 12, 31).unwrap();
+        let (inicio, fin) = rango_reserva(fecha, hora(22, 0), hora(2, 0));
+        assert_eq!(inicio, fecha.and_time(hora(22, 0)));
+        assert_eq!(fin, NaiveDate::from_ymd_opt(2027, 1, 1).unwrap().and_time(hora(2, 0)));
+
+        let (_, fin) = rango_reserva(fecha, hora(22, 0), hora(0, 0));
+        assert_eq!(fin, NaiveDate::from_ymd_opt(2027, 1, 1).unwrap().and_time(hora(0, 0)));
+    }
+
+    #[test]
+    fn rechaza_la_misma_hora_de_inicio_y_fin() {
+        let error = validar_horas(hora(12, 0), hora(12, 0)).unwrap_err();
+        assert_eq!(error.status(), rocket::http::Status::UnprocessableEntity);
+        assert_eq!(error.into_body().details.unwrap()["campo"], "hora_fin");
+
+        assert!(validar_horas(hora(22, 0), hora(2, 0)).is_ok());
+    }
 }
@@ -1,4 +1,5 @@
 use rocket::tokio::sync::broadcast;
+use serde::Serialize;
 
 /// 📡 Módulo de WebSocket (SSE en este caso)
 pub mod ws; // 👈 Esto expone el archivo ws.rs
@@ -20,4 +21,11 @@ impl Broadcaster {
     pub fn send(&self, msg: &str) {
         let _ = self.sender.send(msg.to_string());
     }
+
+    /// Envía un evento con datos como JSON `{"evento": ..., "datos": ...}`;
+    /// los clientes que solo esperan "actualizar" lo ignoran
+    pub fn send_evento<T: Serialize>(&self, evento: &str, datos: &T) {
+        let msg = serde_json::json!({ "evento": evento, "datos": datos });
+        self.send(&msg.to_string());
+    }
 }
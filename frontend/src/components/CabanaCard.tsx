@@ -4,11 +4,21 @@ interface Props {
   cabana: Cabana;
 }
 
+const hora = (instante: string) => instante.slice(11, 16);
+
 export default function CabanaCard({ cabana }: Props) {
-  const color =
-    cabana.estado === "disponible"
-      ? "border-green-500"
-      : "border-red-500 opacity-75";
+  const ocupacion = cabana.ocupacion_actual;
+  const libre = cabana.estado === "activa" && ocupacion.estado === "libre";
+  const color = libre ? "border-green-500" : "border-red-500 opacity-75";
+
+  const etiqueta =
+    cabana.estado !== "activa"
+      ? "⛔ Fuera de servicio"
+      : ocupacion.estado === "mantenimiento"
+        ? "🛠️ En mantenimiento"
+        : ocupacion.estado === "ocupada"
+          ? "🔴 Ocupada"
+          : "🟢 Disponible";
 
   return (
     <div
@@ -30,10 +40,21 @@ export default function CabanaCard({ cabana }: Props) {
       )}
 
       <p>
-        <b>Estado:</b>{" "}
-        {cabana.estado === "disponible" ? "🟢 Disponible" : "🔴 Ocupada"}
+        <b>Estado:</b> {etiqueta}
+        {ocupacion.reserva_actual &&
+          ` hasta las ${hora(ocupacion.reserva_actual.fin)}`}
+        {ocupacion.mantenimiento_actual &&
+          ` (${ocupacion.mantenimiento_actual.motivo})`}
       </p>
 
+      {ocupacion.proxima_reserva && (
+        <p>
+          <b>Próxima reserva:</b>{" "}
+          {ocupacion.proxima_reserva.inicio.slice(0, 10)}{" "}
+          {hora(ocupacion.proxima_reserva.inicio)}
+        </p>
+      )}
+
       {cabana.precio_hora && (
         <p>
           <b>Precio/hora:</b> {cabana.precio_hora} Bs
@@ -2,6 +2,19 @@ import { useEffect, useState } from "react";
 import { api } from "../api/api";
 import CabanaCard from "../components/CabanaCard";
 
+export interface ReservaResumen {
+  id: number;
+  inicio: string;
+  fin: string;
+}
+
+export interface OcupacionActual {
+  estado: "libre" | "ocupada" | "mantenimiento";
+  reserva_actual?: ReservaResumen | null;
+  proxima_reserva?: ReservaResumen | null;
+  mantenimiento_actual?: { fin: string; motivo: string } | null;
+}
+
 export interface Cabana {
   id: number;
   nombre: string;
@@ -10,6 +23,7 @@ export interface Cabana {
   estado: string;
   descripcion?: string;
   precio_hora?: number;
+  ocupacion_actual: OcupacionActual;
 }
 
 export default function CabanasPage() {
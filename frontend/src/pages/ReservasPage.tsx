@@ -1,4 +1,4 @@
-import { useEffect, useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import { api } from "../api/api";
 import "./ReservasPage.css";
 
@@ -10,36 +10,56 @@ interface Reserva {
   hora_inicio: string;
   hora_fin: string;
   estado: string;
+  num_personas: number;
   observaciones?: string;
 }
 
+interface PaginaReservas {
+  items: Reserva[];
+  total: number;
+  limit: number;
+  offset: number;
+}
+
 interface NuevaReserva {
   cliente_id: number;
   cabana_id: number;
   fecha_reserva: string;
   hora_inicio: string;
   hora_fin: string;
+  num_personas: number;
   observaciones?: string;
 }
 
+// Filas por página del listado (el backend admite hasta 500)
+const POR_PAGINA = 50;
+
 export default function ReservasPage() {
   const [reservas, setReservas] = useState<Reserva[]>([]);
+  const [total, setTotal] = useState(0);
+  const [offset, setOffset] = useState(0);
+  // El handler de SSE se registra una sola vez: lee la página actual desde aquí
+  const offsetRef = useRef(0);
   const [nuevaReserva, setNuevaReserva] = useState<NuevaReserva>({
     cliente_id: 1,
     cabana_id: 1,
     fecha_reserva: "",
     hora_inicio: "",
     hora_fin: "",
+    num_personas: 2,
     observaciones: "",
   });
   const [loading, setLoading] = useState(true);
   const [ultimaActualizacion, setUltimaActualizacion] = useState<string>("");
 
-  // 🔁 Cargar reservas desde backend
+  // 🔁 Cargar reservas desde backend (las más recientes primero)
   const cargar = async () => {
     try {
-      const res = await api.get<Reserva[]>("/reservas");
-      setReservas(res.data);
+      const res = await api.get<PaginaReservas>("/reservas", {
+        params: { orden: "desc", limit: POR_PAGINA, offset: offsetRef.current },
+      });
+      setReservas(res.data.items);
+      setTotal(res.data.total);
       setLoading(false);
       setUltimaActualizacion(new Date().toLocaleTimeString());
     } catch (err) {
@@ -57,15 +77,23 @@ export default function ReservasPage() {
         fecha_reserva: "",
         hora_inicio: "",
         hora_fin: "",
+        num_personas: 2,
         observaciones: "",
       });
       await cargar(); // refresca lista sin esperar SSE
     } catch (err: any) {
       console.error("❌ Error al crear reserva:", err.response?.data || err);
-      alert(err.response?.data?.error || "Error al crear reserva");
+      alert(err.response?.data?.message || "Error al crear reserva");
     }
   };
 
+  // 📄 Cambiar de página
+  const irA = (nuevoOffset: number) => {
+    offsetRef.current = Math.max(0, nuevoOffset);
+    setOffset(offsetRef.current);
+    void cargar();
+  };
+
   // 🗑️ Eliminar reserva
   const eliminarReserva = async (id: number) => {
     if (!window.confirm("¿Seguro que deseas eliminar esta reserva?")) return;
@@ -167,6 +195,18 @@ export default function ReservasPage() {
               })
             }
           />
+          <input
+            type="number"
+            min={1}
+            placeholder="Personas"
+            value={nuevaReserva.num_personas}
+            onChange={(e) =>
+              setNuevaReserva({
+                ...nuevaReserva,
+                num_personas: parseInt(e.target.value),
+              })
+            }
+          />
           <input
             type="text"
             placeholder="Observaciones (opcional)"
@@ -201,6 +241,7 @@ export default function ReservasPage() {
                 <th>Fecha</th>
                 <th>Hora Inicio</th>
                 <th>Hora Fin</th>
+                <th>Personas</th>
                 <th>Estado</th>
                 <th>Acción</th>
               </tr>
@@ -217,6 +258,7 @@ export default function ReservasPage() {
                     </td>
                     <td>{r.hora_inicio}</td>
                     <td>{r.hora_fin}</td>
+                    <td>{r.num_personas}</td>
                     <td>
                       <span
                         className={`estado-badge ${r.estado
@@ -238,12 +280,29 @@ export default function ReservasPage() {
                 ))
               ) : (
                 <tr>
-                  <td colSpan={8}>No hay reservas registradas.</td>
+                  <td colSpan={9}>No hay reservas registradas.</td>
                 </tr>
               )}
             </tbody>
           </table>
         )}
+
+        {total > POR_PAGINA && (
+          <div className="paginacion">
+            <button disabled={offset === 0} onClick={() => irA(offset - POR_PAGINA)}>
+              ← Más recientes
+            </button>
+            <span>
+              {offset + 1}–{Math.min(offset + POR_PAGINA, total)} de {total}
+            </span>
+            <button
+              disabled={offset + POR_PAGINA >= total}
+              onClick={() => irA(offset + POR_PAGINA)}
+            >
+              Anteriores →
+            </button>
+          </div>
+        )}
       </div>
     </div>
   );